@@ -1,14 +1,31 @@
+use dateo_fun_remover::finder::options::SolveOptions;
 use dateo_fun_remover::finder::solve as finder_solve;
 use dateo_fun_remover::inputs::get_goal_and_nums;
 use wasm_bindgen::prelude::*;
 
+// the page shows solutions as they come, so find a first one quickly
+fn solve_options() -> SolveOptions {
+    SolveOptions {
+        anytime: true,
+        ..SolveOptions::default()
+    }
+}
+
+// errors are thrown in javascript with the message the cli would print
+fn to_js_error(err: impl std::fmt::Display) -> JsError {
+    JsError::new(&err.to_string())
+}
+
 #[wasm_bindgen]
-pub fn solve_with_date(year: u32, month: u32, day: u32) {
-    let (goal, nums) = get_goal_and_nums(None, None, Some(day), Some(month), Some(year));
-    finder_solve(&nums, goal, |score, atom| {
+pub fn solve_with_date(year: u32, month: u32, day: u32) -> Result<(), JsError> {
+    let (goal, nums) =
+        get_goal_and_nums(None, None, Some(day), Some(month), Some(year)).map_err(to_js_error)?;
+    finder_solve(&nums, goal, &solve_options(), |score, atom| {
         sendNextSolution(format!("{}", score), format!("{}", atom));
-    });
+    })
+    .map_err(to_js_error)?;
     doneSolving();
+    Ok(())
 }
 #[wasm_bindgen]
 pub fn solve_with_goal_and_nums(
@@ -18,12 +35,14 @@ pub fn solve_with_goal_and_nums(
     num_3: f64,
     num_4: f64,
     num_5: f64,
-) {
+) -> Result<(), JsError> {
     let nums = vec![num_1, num_2, num_3, num_4, num_5];
-    finder_solve(&nums, goal, |score, atom| {
+    finder_solve(&nums, goal, &solve_options(), |score, atom| {
         sendNextSolution(format!("{}", score), format!("{}", atom));
-    });
+    })
+    .map_err(to_js_error)?;
     doneSolving();
+    Ok(())
 }
 
 #[wasm_bindgen]
@@ -1,23 +1,33 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use datoe_fun_remover::finder::atom::Atom;
-use datoe_fun_remover::finder::func::Func;
-use datoe_fun_remover::finder::operation::Operation;
-use datoe_fun_remover::finder::{create_atom_store, get_solution_with_score};
+use dateo_fun_remover::finder::atom::Atom;
+use dateo_fun_remover::finder::func::Func;
+use dateo_fun_remover::finder::joiner::{get_joiners, AtomFilter, Memo};
+use dateo_fun_remover::finder::operation::Operation;
+use dateo_fun_remover::finder::options::Depth;
+use dateo_fun_remover::finder::rules::Rules;
+use dateo_fun_remover::parser::parse_atom;
 use strum::IntoEnumIterator;
 
 fn find_bench(c: &mut Criterion) {
-    let nums: Vec<f64> = vec![-16., -10., 2., 13., 16.];
-    c.bench_function("create_atom_store", |b| {
+    let nums: Vec<(u8, f64)> = [-16., -10., 2., 13., 16.]
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| (i as u8, n))
+        .collect();
+    c.bench_function("joiner_solve_3", |b| {
         b.iter(|| {
-            create_atom_store(black_box(&nums));
-        })
-    });
-
-    let store = create_atom_store(&vec![-16., -10., 2., 13., 16.]);
-    c.bench_function("get_solution_with_score", |b| {
-        b.iter(|| {
-            for i in 0..8 {
-                get_solution_with_score(black_box(i), black_box(19.), black_box(&store));
+            let mut memo = Memo::new();
+            for mut joiner in get_joiners(3).unwrap() {
+                joiner
+                    .solve(
+                        black_box(&nums[..3]),
+                        black_box(19.),
+                        Depth::both(3),
+                        Rules::standard(),
+                        AtomFilter::None,
+                        &mut memo,
+                    )
+                    .for_each(drop);
             }
         })
     });
@@ -25,28 +35,37 @@ fn find_bench(c: &mut Criterion) {
 
 fn atom_bench(c: &mut Criterion) {
     let atom_single = Atom::new_express(1., 2., Operation::Add);
-    let funcs = &[];
-    let distribution = &[];
-    c.bench_function("atom_single_eval", |b| {
-        b.iter(|| atom_single.eval_with_funcs(funcs, distribution))
-    });
+    c.bench_function("atom_single_eval", |b| b.iter(|| atom_single.test(3.)));
 
     let atom_double = Atom::new_express(
         Atom::new_express(1., 2., Operation::Add),
         Atom::new_express(3., 4., Operation::Add),
         Operation::Add,
     );
-    c.bench_function("atom_double_eval", |b| {
-        b.iter(|| atom_double.eval_with_funcs(funcs, distribution))
-    });
+    c.bench_function("atom_double_eval", |b| b.iter(|| atom_double.test(10.)));
 
     let atom_none = Atom::new_express(
         Atom::new_express(1., 2., Operation::Add),
         Atom::new_express(4., 0., Operation::Divide),
         Operation::Add,
     );
-    c.bench_function("atom_none_eval", |b| {
-        b.iter(|| atom_none.eval_with_funcs(funcs, distribution))
+    c.bench_function("atom_none_eval", |b| b.iter(|| atom_none.test(3.)));
+
+    // a squares solution, with the functions the necessity check has to remove
+    let atom_squares = parse_atom("²√²√²√²√(²√(4 + 12) ^ (13 + Σ²√²√(-2 - -18)))").unwrap();
+    c.bench_function("atom_squares_test_tree", |b| {
+        b.iter(|| black_box(&atom_squares).test(4.))
+    });
+    c.bench_function("atom_squares_test_program", |b| {
+        b.iter(|| black_box(&atom_squares).test_by_program(4.))
+    });
+    let atom_funcs = parse_atom("(5 - 13) ^ ²√(4 + 12) - ²√²√Σ16").unwrap();
+    let goal = atom_funcs.value().unwrap();
+    c.bench_function("atom_funcs_test_tree", |b| {
+        b.iter(|| black_box(&atom_funcs).test(goal))
+    });
+    c.bench_function("atom_funcs_test_program", |b| {
+        b.iter(|| black_box(&atom_funcs).test_by_program(goal))
     });
 }
 
@@ -81,7 +100,7 @@ fn function_bench(c: &mut Criterion) {
 
 criterion_group!(
   name = find;
-  config = Criterion::default().sample_size(10000);
+  config = Criterion::default().sample_size(10);
   targets = find_bench
 );
 criterion_group!(atom, atom_bench);
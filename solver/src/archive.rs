@@ -0,0 +1,126 @@
+use crate::error::InputError;
+use crate::finder::SolveReport;
+use crate::seeding::Seeding;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+// a puzzle to solve, whether from a date or given directly
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub goal: f64,
+    pub nums: Vec<f64>,
+    // None if both the numbers and the goal were given
+    pub date: Option<NaiveDate>,
+}
+
+impl Puzzle {
+    // the date whose own puzzle this is, not one with other numbers or goal
+    pub fn own_date(&self, seeding: &dyn Seeding) -> Option<NaiveDate> {
+        self.date.filter(|date| {
+            seeding.goal(*date) == Some(self.goal)
+                && seeding.nums(*date).as_ref() == Some(&self.nums)
+        })
+    }
+}
+
+// one solved puzzle, kept as a line of json so the archive can be appended to
+// and read back with any json tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub solved_at: DateTime<Utc>,
+    // None if the numbers and goal were given directly
+    pub date: Option<NaiveDate>,
+    pub goal: f64,
+    pub nums: Vec<f64>,
+    // 0 if nothing was found
+    pub best_score: u8,
+    pub best: Option<String>,
+    pub completeness: String,
+}
+
+impl ArchiveEntry {
+    // best is the best solution's expression, if there is one
+    pub fn new(puzzle: &Puzzle, report: &SolveReport, best: Option<String>) -> Self {
+        Self {
+            solved_at: Utc::now(),
+            date: puzzle.date,
+            goal: puzzle.goal,
+            nums: puzzle.nums.clone(),
+            best_score: report.best_score,
+            best,
+            completeness: report.completeness.to_string(),
+        }
+    }
+    pub fn puzzle(&self) -> Puzzle {
+        Puzzle {
+            goal: self.goal,
+            nums: self.nums.clone(),
+            date: self.date,
+        }
+    }
+}
+
+pub fn append_to_archive(path: &Path, entry: &ArchiveEntry) -> Result<(), InputError> {
+    let invalid_file = |err: std::io::Error| InputError::InvalidFile {
+        path: path.display().to_string(),
+        reason: err.to_string(),
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(invalid_file)?;
+    writeln!(file, "{}", serde_json::to_string(entry).unwrap()).map_err(invalid_file)
+}
+
+pub fn read_archive(archive: &str) -> Result<Vec<ArchiveEntry>, String> {
+    archive
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|err| format!("line {}: {}", i + 1, err))
+        })
+        .collect()
+}
+
+// the best entry for each date's own puzzle, so results outlive the process
+// that found them. an archive that doesn't exist yet has none
+pub fn best_by_date(
+    path: &Path,
+    seeding: &dyn Seeding,
+) -> Result<HashMap<NaiveDate, ArchiveEntry>, InputError> {
+    let invalid_file = |reason: String| InputError::InvalidFile {
+        path: path.display().to_string(),
+        reason,
+    };
+    let archive = match std::fs::read_to_string(path) {
+        Ok(archive) => archive,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(invalid_file(err.to_string())),
+    };
+    let mut best = HashMap::new();
+    for entry in read_archive(&archive).map_err(invalid_file)? {
+        if let Some(date) = entry.puzzle().own_date(seeding) {
+            keep_best(&mut best, date, entry);
+        }
+    }
+    Ok(best)
+}
+
+// replaces the date's entry only with a better score
+pub fn keep_best(
+    best: &mut HashMap<NaiveDate, ArchiveEntry>,
+    date: NaiveDate,
+    entry: ArchiveEntry,
+) {
+    if best
+        .get(&date)
+        .is_none_or(|old| entry.best_score > old.best_score)
+    {
+        best.insert(date, entry);
+    }
+}
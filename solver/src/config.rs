@@ -0,0 +1,102 @@
+use crate::error::InputError;
+use crate::finder::func::Func;
+use crate::finder::options::Preset;
+use crate::finder::rules::{OperationGroup, RulePreset};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use strum_macros::{Display, EnumString};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, EnumString, Display, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    // one json object per line, for scripts
+    Json,
+}
+
+// defaults for the options that would otherwise be typed every time, e.g.
+//   preset = "quick"
+//   disable-functions = ["factorial"]
+// every field is optional, and flags given on the command line replace the file's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub format: Option<OutputFormat>,
+    pub preset: Option<Preset>,
+    // an IANA name or a UTC offset, like --tz
+    pub tz: Option<String>,
+    pub rules: Option<RulePreset>,
+    // operation groups to turn off
+    pub disable: Vec<OperationGroup>,
+    pub disable_functions: Vec<Func>,
+    // file that every solve appends its result to
+    pub archive: Option<PathBuf>,
+}
+
+impl Config {
+    // $XDG_CONFIG_HOME/dateo-fun-remover/config.toml, or under ~/.config
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("dateo-fun-remover").join("config.toml"))
+    }
+    // a given path has to exist, the default one doesn't. also returns the path
+    // that was read, if any
+    pub fn load(path: Option<&Path>) -> Result<(Self, Option<PathBuf>), InputError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok((Self::default(), None)),
+            },
+        };
+        let invalid_file = |reason: String| InputError::InvalidFile {
+            path: path.display().to_string(),
+            reason,
+        };
+        let config = std::fs::read_to_string(&path).map_err(|err| invalid_file(err.to_string()))?;
+        let config = Self::parse(&config).map_err(invalid_file)?;
+        Ok((config, Some(path)))
+    }
+    pub fn parse(config: &str) -> Result<Self, String> {
+        toml::from_str(config).map_err(|err| err.to_string())
+    }
+    // the other config's settings win wherever it has them
+    pub fn overridden_by(self, other: Config) -> Config {
+        Config {
+            format: other.format.or(self.format),
+            preset: other.preset.or(self.preset),
+            tz: other.tz.or(self.tz),
+            rules: other.rules.or(self.rules),
+            disable: list_or(other.disable, self.disable),
+            disable_functions: list_or(other.disable_functions, self.disable_functions),
+            archive: other.archive.or(self.archive),
+        }
+    }
+    // fills in what the solver uses when nothing is set
+    pub fn with_defaults(self) -> Config {
+        Config {
+            format: Some(self.format.unwrap_or_default()),
+            preset: Some(self.preset.unwrap_or(Preset::Normal)),
+            rules: Some(self.rules.unwrap_or(RulePreset::Standard)),
+            ..self
+        }
+    }
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+}
+
+fn list_or<T>(other: Vec<T>, this: Vec<T>) -> Vec<T> {
+    if other.is_empty() {
+        this
+    } else {
+        other
+    }
+}
@@ -0,0 +1,245 @@
+use crate::error::SolveError;
+use crate::finder::atom::Atom;
+use crate::finder::options::{Engine, Preset, SearchOptions, SolveOptions};
+use crate::finder::solve;
+use crate::inputs::{check_nums, get_goal_and_nums};
+use chrono::{Datelike, NaiveDate};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+// puzzles with the best score the solver is known to reach, one per line:
+//   date 2023-03-08 normal 19
+//   nums -18 -2 9 11 20 goal 7 quick 19
+// a nums entry has 1 to 5 numbers, like the command line.
+// the preset is part of the entry so slow puzzles can still be checked quickly.
+// blank lines and lines starting with # are skipped
+#[derive(Debug, Clone)]
+pub struct CorpusEntry {
+    // how the puzzle was written in the corpus
+    pub name: String,
+    pub goal: f64,
+    pub nums: Vec<f64>,
+    pub preset: Preset,
+    pub best_score: u8,
+}
+
+pub fn parse_corpus(corpus: &str) -> Result<Vec<CorpusEntry>, String> {
+    corpus
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| parse_entry(line).map_err(|err| format!("line {}: {}", i + 1, err)))
+        .collect()
+}
+
+fn parse_entry(line: &str) -> Result<CorpusEntry, String> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let (name, goal, nums, rest) = match words.as_slice() {
+        ["date", date, rest @ ..] => {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|err| format!("bad date {}: {}", date, err))?;
+            let (goal, nums) = get_goal_and_nums(
+                None,
+                None,
+                Some(date.day()),
+                Some(date.month()),
+                Some(date.year() as u32),
+            )
+            .map_err(|err| err.to_string())?;
+            (format!("date {}", date), goal, nums, rest)
+        }
+        ["nums", rest @ ..] => {
+            let Some(goal_at) = rest.iter().position(|word| *word == "goal") else {
+                return Err("expected goal after the nums".to_string());
+            };
+            let nums = rest[..goal_at]
+                .iter()
+                .map(|num| parse_num(num))
+                .collect::<Result<Vec<_>, _>>()?;
+            let nums = check_nums(nums).map_err(|err| err.to_string())?;
+            let Some(goal) = rest.get(goal_at + 1) else {
+                return Err("expected a number after goal".to_string());
+            };
+            let goal = parse_num(goal)?;
+            (
+                words[..goal_at + 3].join(" "),
+                goal,
+                nums,
+                &rest[goal_at + 2..],
+            )
+        }
+        _ => return Err(format!("expected a date or nums entry, got {}", line)),
+    };
+    let [preset, best_score] = rest else {
+        return Err("expected a preset and a best score after the puzzle".to_string());
+    };
+    Ok(CorpusEntry {
+        name,
+        goal,
+        nums,
+        preset: preset
+            .parse()
+            .map_err(|_| format!("unknown preset {}", preset))?,
+        best_score: best_score
+            .parse()
+            .map_err(|_| format!("bad best score {}", best_score))?,
+    })
+}
+
+fn parse_num(num: &str) -> Result<f64, String> {
+    num.parse().map_err(|_| format!("bad number {}", num))
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub entry: CorpusEntry,
+    // 0 if nothing was found
+    pub best_score: u8,
+    // solutions the solver returned that don't hit the goal under the rules
+    pub rejected: Vec<Atom>,
+}
+
+impl CheckResult {
+    pub fn is_regression(&self) -> bool {
+        self.best_score < self.entry.best_score || !self.rejected.is_empty()
+    }
+    // the corpus should be updated to the new score
+    pub fn is_improvement(&self) -> bool {
+        self.best_score > self.entry.best_score
+    }
+}
+
+pub fn check_entry(entry: &CorpusEntry) -> Result<CheckResult, SolveError> {
+    let result = solve_entry(entry, Engine::Joiner)?;
+    Ok(CheckResult {
+        entry: entry.clone(),
+        best_score: result.best_score,
+        rejected: result.rejected,
+    })
+}
+
+// what one engine found for a corpus puzzle
+#[derive(Debug, Clone)]
+pub struct EngineResult {
+    pub engine: Engine,
+    // 0 if nothing was found
+    pub best_score: u8,
+    pub best: Option<Atom>,
+    pub rejected: Vec<Atom>,
+}
+
+fn solve_entry(entry: &CorpusEntry, engine: Engine) -> Result<EngineResult, SolveError> {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(entry.preset),
+        engine,
+        ..SolveOptions::default()
+    };
+    let best = RefCell::new(None);
+    let rejected = RefCell::new(Vec::new());
+    let report = solve(&entry.nums, entry.goal, &options, |_, atom| {
+        if !atom.verify(entry.goal, &options.rules) {
+            rejected.borrow_mut().push(atom.clone());
+        }
+        *best.borrow_mut() = Some(atom.clone());
+    })?;
+    Ok(EngineResult {
+        engine,
+        best_score: report.best_score,
+        best: best.into_inner(),
+        rejected: rejected.into_inner(),
+    })
+}
+
+// both engines on the same puzzle at its preset. the legacy engine searches
+// less and has no strategies, so the joiners usually score higher
+#[derive(Debug, Clone)]
+pub struct DiffResult {
+    pub entry: CorpusEntry,
+    pub joiner: EngineResult,
+    pub legacy: EngineResult,
+}
+
+impl DiffResult {
+    // the engine with the higher score, if they differ
+    pub fn ahead(&self) -> Option<&EngineResult> {
+        match self.joiner.best_score.cmp(&self.legacy.best_score) {
+            Ordering::Greater => Some(&self.joiner),
+            Ordering::Less => Some(&self.legacy),
+            Ordering::Equal => None,
+        }
+    }
+    // the engines scoring differently, or either giving a solution that
+    // doesn't hold
+    pub fn is_disagreement(&self) -> bool {
+        self.ahead().is_some()
+            || !self.joiner.rejected.is_empty()
+            || !self.legacy.rejected.is_empty()
+    }
+    // the disagreements diff-engines fails on: the legacy engine beating the
+    // joiners, or a solution that doesn't hold. the joiners being ahead is
+    // expected, so it's only reported
+    pub fn is_failure(&self) -> bool {
+        self.legacy.best_score > self.joiner.best_score
+            || !self.joiner.rejected.is_empty()
+            || !self.legacy.rejected.is_empty()
+    }
+}
+
+pub fn diff_entry(entry: &CorpusEntry) -> Result<DiffResult, SolveError> {
+    Ok(DiffResult {
+        entry: entry.clone(),
+        joiner: solve_entry(entry, Engine::Joiner)?,
+        legacy: solve_entry(entry, Engine::Legacy)?,
+    })
+}
+
+impl Display for DiffResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entry = &self.entry;
+        write!(
+            f,
+            "{} ({}): joiner {}, legacy {}",
+            entry.name, entry.preset, self.joiner.best_score, self.legacy.best_score
+        )?;
+        if let Some(ahead) = self.ahead() {
+            write!(f, ", {} ahead", ahead.engine)?;
+            if let Some(best) = &ahead.best {
+                write!(f, "\n  {} solution: {}", ahead.engine, best)?;
+            }
+        }
+        for result in [&self.joiner, &self.legacy] {
+            for atom in &result.rejected {
+                write!(f, "\n  rejected {} solution: {}", result.engine, atom)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for CheckResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let entry = &self.entry;
+        write!(f, "{} ({}): ", entry.name, entry.preset)?;
+        if self.best_score < entry.best_score {
+            write!(
+                f,
+                "best score dropped from {} to {}",
+                entry.best_score, self.best_score
+            )?;
+        } else if self.is_improvement() {
+            write!(
+                f,
+                "best score rose from {} to {}, update the corpus",
+                entry.best_score, self.best_score
+            )?;
+        } else {
+            write!(f, "best score {}", self.best_score)?;
+        }
+        for atom in &self.rejected {
+            write!(f, "\n  rejected solution: {}", atom)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::NaiveDate;
+use std::fmt::{Display, Formatter};
+
+// something wrong with what the user asked for
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputError {
+    // a number that couldn't be read
+    InvalidNumber(String),
+    // the solver takes 1 to 5 numbers
+    WrongNumberCount(usize),
+    // infinite, NaN, or too big for the solver to work with
+    OutOfRange(f64),
+    InvalidDate { year: u32, month: u32, day: u32 },
+    // a full date that isn't YYYY-MM-DD or one of the relative forms
+    UnknownDate(String),
+    // not an IANA timezone name or a UTC offset
+    UnknownTimeZone(String),
+    // the seeding formula couldn't make a puzzle for the date
+    Unseedable(NaiveDate),
+    // a file the command needs couldn't be read or makes no sense
+    InvalidFile { path: String, reason: String },
+    // the server couldn't take the address, e.g. because the port is in use
+    CantListen { address: String, reason: String },
+}
+
+// something that stops a search from running
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    Input(InputError),
+    // the tree shapes only go up to 5 numbers
+    UnsupportedNumberCount(usize),
+    // a tree shape string that can't be read
+    InvalidTree(String),
+}
+
+impl InputError {
+    // distinct so scripts can tell what went wrong
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            InputError::InvalidNumber(_) => 10,
+            InputError::WrongNumberCount(_) => 11,
+            InputError::OutOfRange(_) => 13,
+            InputError::InvalidDate { .. } => 14,
+            InputError::InvalidFile { .. } => 15,
+            InputError::UnknownDate(_) => 16,
+            InputError::UnknownTimeZone(_) => 17,
+            InputError::Unseedable(_) => 18,
+            InputError::CantListen { .. } => 19,
+        }
+    }
+}
+
+impl SolveError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SolveError::Input(err) => err.exit_code(),
+            SolveError::UnsupportedNumberCount(_) => 20,
+            SolveError::InvalidTree(_) => 21,
+        }
+    }
+}
+
+impl From<InputError> for SolveError {
+    fn from(err: InputError) -> Self {
+        SolveError::Input(err)
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::InvalidNumber(num) => write!(
+                f,
+                "'{}' isn't a number, please provide a space separated list of numbers, e.g. '1 2 3 4 5'",
+                num
+            ),
+            InputError::WrongNumberCount(count) => {
+                write!(f, "please provide 1 to 5 numbers, got {}", count)
+            }
+            InputError::OutOfRange(num) => write!(f, "{} is too big to solve with", num),
+            InputError::InvalidDate { year, month, day } => {
+                write!(f, "{}-{:02}-{:02} isn't a real date", year, month, day)
+            }
+            InputError::InvalidFile { path, reason } => write!(f, "{}: {}", path, reason),
+            InputError::UnknownDate(date) => write!(
+                f,
+                "couldn't read the date '{}', use YYYY-MM-DD, today, yesterday, tomorrow, +N, -N or a weekday",
+                date
+            ),
+            InputError::Unseedable(date) => write!(
+                f,
+                "the seeding formula can't find enough different numbers for {}",
+                date
+            ),
+            InputError::CantListen { address, reason } => {
+                write!(f, "couldn't listen on {}: {}", address, reason)
+            }
+            InputError::UnknownTimeZone(zone) => write!(
+                f,
+                "unknown timezone '{}', use an IANA name like Europe/Berlin or an offset like +05:30",
+                zone
+            ),
+        }
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Input(err) => write!(f, "{}", err),
+            SolveError::UnsupportedNumberCount(count) => {
+                write!(f, "can only solve with 1 to 5 numbers, got {}", count)
+            }
+            SolveError::InvalidTree(tree) => write!(f, "invalid tree shape: {}", tree),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+impl std::error::Error for SolveError {}
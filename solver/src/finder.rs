@@ -1,221 +1,558 @@
 pub mod atom;
+pub mod bounds;
+pub mod family;
 pub mod func;
 pub mod func_list;
 pub mod joiner;
+pub mod js_eval;
+pub mod legacy;
 pub mod math;
+pub mod near_miss;
 pub mod operation;
+pub mod options;
+pub mod program;
+pub mod rules;
 pub mod score;
+pub mod stats;
+pub mod stop;
+pub mod strategy;
 pub mod tree;
 pub mod tree_shapes;
 
-use func::Func;
+use crate::error::{InputError, SolveError};
+use bounds::{Bound, BoundSet, Completeness};
+use family::Family;
 use itertools::Itertools;
 use joiner::get_joiners;
 use joiner::{AtomFilter, Memo};
+use near_miss::{find_near_miss, NearMiss};
+use options::{Depth, Engine, SearchOptions, SolveOptions};
+use rules::{FamilyRanking, Rules};
 use score::Score;
+use stats::SolveStats;
+use strategy::{StrategyKind, StrategyReport};
 
 use crate::finder::atom::Atom;
-use crate::finder::operation::Operation;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use tracing::{debug, debug_span, info, info_span};
 
-const SQUARES_DEPTH: usize = 4;
-const DEPTH: usize = 5;
+pub struct SolveReport {
+    // 0 if no solution was found
+    pub best_score: u8,
+    // only searched for when no solution was found
+    pub near_miss: Option<NearMiss>,
+    pub completeness: Completeness,
+    pub strategies: Vec<StrategyReport>,
+    // best ranked first
+    pub families: Vec<Family>,
+    pub stats: SolveStats,
+}
+
+impl SolveReport {
+    // best score once families are ranked by the rules
+    pub fn ranked_score(&self, ranking: FamilyRanking) -> u8 {
+        self.families
+            .iter()
+            .map(|family| family.rank(ranking))
+            .fold(self.best_score, u8::max)
+    }
+    // everything but the solutions, which are given as they're found
+    pub fn to_json(&self, ranking: FamilyRanking) -> serde_json::Value {
+        serde_json::json!({
+            "best_score": self.best_score,
+            "ranked_score": self.ranked_score(ranking),
+            "families": self.families.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "strategies": self.strategies.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "near_miss": self.near_miss.as_ref().map(ToString::to_string),
+            "completeness": self.completeness.to_string(),
+            "stats": self.stats.to_json(),
+        })
+    }
+}
+
+pub fn solve(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+    callback: impl Fn(Score, &Atom),
+) -> Result<SolveReport, SolveError> {
+    solve_warm(nums, goal, options, &mut WarmMemos::new(), callback)
+}
+
+// a memo past this many entries is dropped before the next solve, so a long
+// running process doesn't keep growing with every goal it's asked about
+const MAX_WARM_ENTRIES: usize = 2_000;
+
+// the search options, rules and tagged numbers the warm memos were filled with
+type FilledWith = (SearchOptions, Rules, Vec<(u8, f64)>);
+
+// the memos of earlier solves, for a long running process to reuse on the same
+// numbers, e.g. the same puzzle again or another goal. a memo is only valid for
+// the search options and rules it was filled with, and one puzzle's memo can
+// take gigabytes, so they start over when those or the numbers change. the
+// limits a memo hit stay with it, the ones hit working back from a goal under
+// that goal, so a warm solve reports the limits its goal hit, and a stop only
+// counts for the solve it stopped
+#[derive(Default)]
+pub struct WarmMemos {
+    filled_with: Option<FilledWith>,
+    strategies: Vec<(StrategyKind, Memo)>,
+    // the general search at the full depth
+    general: Memo,
+}
 
-pub fn solve(nums: &[f64], goal: f64, callback: impl Fn(Score, &Atom)) {
+impl WarmMemos {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.general.len()
+            + self
+                .strategies
+                .iter()
+                .map(|(_, memo)| memo.len())
+                .sum::<usize>()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn prepare(&mut self, nums: &[(u8, f64)], options: &SolveOptions) {
+        let filled_with = Some((options.search, options.rules, nums.to_vec()));
+        if self.filled_with != filled_with || self.len() > MAX_WARM_ENTRIES {
+            *self = Self {
+                filled_with,
+                ..Self::default()
+            };
+        }
+    }
+    fn strategy(&mut self, kind: StrategyKind) -> &mut Memo {
+        let i = match self.strategies.iter().position(|(k, _)| *k == kind) {
+            Some(i) => i,
+            None => {
+                self.strategies.push((kind, Memo::new()));
+                self.strategies.len() - 1
+            }
+        };
+        &mut self.strategies[i].1
+    }
+}
+
+// like solve, but reusing the memos of earlier solves
+pub fn solve_warm(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+    memos: &mut WarmMemos,
+    callback: impl Fn(Score, &Atom),
+) -> Result<SolveReport, SolveError> {
+    // the legacy engine has no memos to keep warm
+    if options.engine == Engine::Legacy {
+        return legacy::solve(nums, goal, options, callback);
+    }
+    let _span = info_span!("solve", goal, ?nums).entered();
+    let nums = tag_nums(nums)?;
+    check_num(goal)?;
+    let _watching = stop::watch(&options.stop);
+    memos.prepare(&nums, options);
     let mut best_score = 0;
-    println!("solving squares");
-    solve_squares(nums, goal, &callback, &mut best_score);
-    println!("solving other");
-    solve_all(nums, goal, &callback, &mut best_score);
+    let mut completeness = Completeness {
+        bounds_hit: BoundSet::new(),
+        best_score: 0,
+    };
+    math::take_limit_hit();
+    stats::start(options.timed);
+    let full_depth = options.search.depth;
+    let depths = search_depths(options);
+    let (shallow_depths, deep_depths) = depths.split_at(depths.len().min(ANYTIME_SHALLOW_LEVELS));
+    if options.anytime {
+        // cheap levels first, so there is an answer before the strategies run
+        for depth in shallow_depths {
+            let level = depth.num.max(depth.goal);
+            let _span = info_span!("general", depth = level).entered();
+            let _timing = stats::time_phase(format!("other at depth {}", level));
+            info!("solving other at depth {}", level);
+            // memo keys don't include the depth, so only the full depth is warm
+            let mut fresh = Memo::new();
+            let memo = if *depth == full_depth {
+                &mut memos.general
+            } else {
+                &mut fresh
+            };
+            solve_all(
+                &nums,
+                goal,
+                *depth,
+                options,
+                memo,
+                &callback,
+                &mut best_score,
+            )?;
+            keep_limit_hit(memo);
+        }
+    }
+    let mut strategies = Vec::new();
+    let mut families = Vec::new();
+    for kind in &options.strategies {
+        let strategy = kind.build();
+        if !strategy.is_allowed(&options.rules) || options.stop.is_stopped() {
+            continue;
+        }
+        let _span = info_span!("strategy", %kind).entered();
+        let _timing = stats::time_phase(kind.to_string());
+        info!("solving {}", kind);
+        let score_before = best_score;
+        let memo = memos.strategy(*kind);
+        let mut result = strategy.solve(&nums, goal, options, memo, &callback, &mut best_score)?;
+        if keep_limit_hit(memo) {
+            result.bounds_hit.insert(Bound::NumSize);
+        }
+        for bound in result.bounds_hit.iter() {
+            completeness.bounds_hit.insert(bound);
+        }
+        families.extend(result.families.iter().cloned());
+        strategies.push(StrategyReport {
+            kind: *kind,
+            best_score: if best_score > score_before {
+                best_score
+            } else {
+                0
+            },
+            bounds_hit: result.bounds_hit,
+            families: result.families,
+        });
+    }
+    if options.anytime {
+        for depth in deep_depths {
+            let level = depth.num.max(depth.goal);
+            let _span = info_span!("general", depth = level).entered();
+            let _timing = stats::time_phase(format!("other at depth {}", level));
+            info!("solving other at depth {}", level);
+            let mut fresh = Memo::new();
+            let memo = if *depth == full_depth {
+                &mut memos.general
+            } else {
+                &mut fresh
+            };
+            solve_all(
+                &nums,
+                goal,
+                *depth,
+                options,
+                memo,
+                &callback,
+                &mut best_score,
+            )?;
+            keep_limit_hit(memo);
+        }
+    } else {
+        let _span = info_span!("general").entered();
+        let _timing = stats::time_phase("other");
+        info!("solving other");
+        solve_all(
+            &nums,
+            goal,
+            full_depth,
+            options,
+            &mut memos.general,
+            &callback,
+            &mut best_score,
+        )?;
+        keep_limit_hit(&mut memos.general);
+    }
+    // only the full depth counts, the anytime levels were cut off by their depth
+    completeness
+        .bounds_hit
+        .extend(memos.general.bounds_for(goal));
+    // checked once at the end, so a stop inside the last joiner counts too
+    if options.stop.is_stopped() {
+        completeness.bounds_hit.insert(Bound::Stopped);
+    }
+    completeness.best_score = best_score;
+    let ranking = options.rules.family_ranking;
+    families.sort_by_key(|family| Reverse(family.rank(ranking)));
+
+    // a stopped search didn't fill the memo the near miss is read from
+    let near_miss = if best_score == 0 && !options.stop.is_stopped() {
+        let _span = info_span!("near_miss").entered();
+        let _timing = stats::time_phase("near misses");
+        info!("solving near misses");
+        Some(find_near_miss(
+            &nums,
+            goal,
+            completeness.bounds_hit,
+            &memos.general,
+        )?)
+    } else {
+        None
+    };
+    Ok(SolveReport {
+        best_score,
+        near_miss,
+        completeness,
+        strategies,
+        families,
+        stats: stats::take(),
+    })
 }
 
-fn solve_all(nums: &[f64], goal: f64, callback: impl Fn(Score, &Atom), best_score: &mut u8) {
-    let mut memo = Memo::new();
-    let nums: &[(u8, f64)] = &nums
+// puts a number size hit since the last call on the memo whose search made it.
+// kept in the memo, since the values it threw away won't be made again
+fn keep_limit_hit(memo: &mut Memo) -> bool {
+    let hit = math::take_limit_hit();
+    if hit {
+        memo.bounds.insert(Bound::NumSize);
+    }
+    hit
+}
+
+// finds the best solution for every goal, reusing the goal independent
+// parts of the memo instead of starting over for each goal
+pub fn solve_goals(
+    nums: &[f64],
+    goals: impl IntoIterator<Item = f64>,
+    options: &SolveOptions,
+    callback: impl Fn(f64, Option<(Score, Atom)>),
+) -> Result<(), SolveError> {
+    let nums = tag_nums(nums)?;
+    let mut strategies = options
+        .strategies
         .iter()
-        .enumerate()
-        .map(|(i, n)| (i as u8, *n))
+        .map(|kind| kind.build())
+        .filter(|strategy| strategy.is_allowed(&options.rules))
+        .map(|strategy| (strategy, Memo::new()))
         .collect_vec();
-    for num_count in (1..=5).rev() {
-        let joiners = get_joiners(num_count);
-        for mut joiner in joiners {
-            for (score, atom) in joiner.solve(
+    let mut memo = Memo::new();
+    for goal in goals {
+        check_num(goal)?;
+        let best = RefCell::new(None);
+        let keep_best = |score: Score, atom: &Atom| {
+            *best.borrow_mut() = Some((score, atom.clone()));
+        };
+        let mut best_score = 0;
+        for (strategy, strategy_memo) in strategies.iter_mut() {
+            strategy.solve(
                 &nums,
                 goal,
-                DEPTH,
-                AtomFilter::MinScore(*best_score),
-                &mut memo,
-            ) {
-                *best_score = score.score();
-                callback(score, &atom);
-            }
+                options,
+                strategy_memo,
+                &keep_best,
+                &mut best_score,
+            )?;
+            // vals that lead to this goal won't be used again
+            strategy_memo.remove_goal(goal);
         }
+        solve_all(
+            &nums,
+            goal,
+            options.search.depth,
+            options,
+            &mut memo,
+            keep_best,
+            &mut best_score,
+        )?;
+        memo.remove_goal(goal);
+        callback(goal, best.into_inner());
     }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub score: Score,
+    pub atom: Atom,
+    // the value the two halves of the search met at, None if a strategy found it
+    pub meeting: Option<f64>,
+}
+
+// the best solution like solve finds it, without the report
+pub fn best_solution(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+) -> Result<Option<Solution>, SolveError> {
+    Ok(best_solution_with_count(nums, goal, options, 0)?.0)
 }
 
-fn solve_squares(nums: &[f64], goal: f64, callback: impl Fn(Score, &Atom), best_score: &mut u8) {
+// also counts the different expressions that hit the goal, stopping at limit.
+// the strategies' solutions count too, and the count fills the memo the best
+// solution is then searched in
+pub fn best_solution_with_count(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+    limit: usize,
+) -> Result<(Option<Solution>, usize), SolveError> {
+    let nums = tag_nums(nums)?;
+    check_num(goal)?;
+    let best = RefCell::new(None);
+    let solutions = RefCell::new(HashSet::new());
+    let mut best_score = 0;
+    for kind in &options.strategies {
+        let strategy = kind.build();
+        if !strategy.is_allowed(&options.rules) {
+            continue;
+        }
+        let mut memo = Memo::new();
+        let keep_best = |score: Score, atom: &Atom| {
+            solutions.borrow_mut().insert(atom.to_string());
+            *best.borrow_mut() = Some(Solution {
+                score,
+                atom: atom.clone(),
+                meeting: None,
+            });
+        };
+        strategy.solve(&nums, goal, options, &mut memo, &keep_best, &mut best_score)?;
+    }
+    let mut solutions = solutions.into_inner();
     let mut memo = Memo::new();
-    let nums: &[(u8, f64)] = &nums
+    let keep_best = |score: Score, atom: &Atom, meeting: f64| {
+        *best.borrow_mut() = Some(Solution {
+            score,
+            atom: atom.clone(),
+            meeting: Some(meeting),
+        });
+    };
+    // every solution is seen while counting, so a count that didn't reach the
+    // limit also found the best one
+    let mut counted_all = limit > solutions.len();
+    if counted_all {
+        'count: for num_count in (1..=nums.len()).rev() {
+            for mut joiner in get_joiners(num_count)? {
+                for (score, atom, meeting) in joiner.solve_with_meeting(
+                    &nums,
+                    goal,
+                    options.search.depth,
+                    options.rules,
+                    AtomFilter::None,
+                    &mut memo,
+                ) {
+                    if score.score() > best_score {
+                        best_score = score.score();
+                        keep_best(score, &atom, meeting);
+                    }
+                    solutions.insert(atom.to_string());
+                    if solutions.len() >= limit {
+                        counted_all = false;
+                        break 'count;
+                    }
+                }
+            }
+        }
+    }
+    if !counted_all {
+        solve_all_with_meeting(
+            &nums,
+            goal,
+            options.search.depth,
+            options,
+            &mut memo,
+            keep_best,
+            &mut best_score,
+        )?;
+    }
+    Ok((best.into_inner(), solutions.len().min(limit)))
+}
+
+// tag each number with its index so equal numbers get different memo keys,
+// after checking the search can handle them
+fn tag_nums(nums: &[f64]) -> Result<Vec<(u8, f64)>, SolveError> {
+    if nums.is_empty() || nums.len() > 5 {
+        return Err(SolveError::UnsupportedNumberCount(nums.len()));
+    }
+    for num in nums {
+        check_num(*num)?;
+    }
+    Ok(nums
         .iter()
         .enumerate()
         .map(|(i, n)| (i as u8, *n))
-        .collect_vec();
-    let combinations = (1..=2)
-        .rev()
-        .flat_map(|split| combinations_when_split(&nums, split));
-
-    for (goal_nums, power_nums) in combinations {
-        for (score, atom) in solve_square(
-            &goal_nums,
-            &power_nums,
-            goal,
-            AtomFilter::MinScore(*best_score),
-            &mut memo,
-        ) {
-            *best_score = score.score();
-            callback(score, &atom);
-        }
+        .collect_vec())
+}
+
+fn check_num(num: f64) -> Result<(), SolveError> {
+    if !math::within_limit(&num) || num.is_nan() {
+        return Err(InputError::OutOfRange(num).into());
     }
+    Ok(())
 }
 
-const POWER_OF_2: [f64; 30] = [
-    1., 2., 4., 8., 16., 32., 64., 128., 256., 512., 1024., 2048., 4096., 8192., 16384., 32768.,
-    65536., 131072., 262144., 524288., 1048576., 2097152., 4194304., 8388608., 16777216.,
-    33554432., 67108864., 134217728., 268435456., 536870912.,
-];
+// how many of the cheapest anytime levels run before the strategies
+const ANYTIME_SHALLOW_LEVELS: usize = 2;
 
-fn solve_square<'a>(
-    goal_nums: &[(u8, f64)],
-    power_nums: &'a [(u8, f64)],
-    goal: f64,
-    mut atom_filter: AtomFilter,
-    memo: &'a mut Memo,
-) -> impl Iterator<Item = (Score, Atom)> + 'a {
-    let goal_joiners = get_joiners(goal_nums.len());
-    let mut power_joiners = get_joiners(power_nums.len());
-
-    let goal_solutions = goal_joiners
-        .into_iter()
-        .flat_map(|mut joiner| {
-            joiner
-                .solve(goal_nums, goal, SQUARES_DEPTH, AtomFilter::None, memo)
-                .into_iter()
-                .collect_vec()
-        })
-        .collect_vec();
-    goal_solutions
-        .into_iter()
-        .flat_map(move |(_goal_score, goal_atom)| {
-            let goal_atom_steps = goal_atom.get_steps_with_eval();
-            let (inner_goal, max_step) = goal_atom_steps
-                .into_iter()
-                .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
-                .unwrap();
-            let (goal_atom_outer, goal_atom_inner) = goal_atom.split(max_step);
-            let (max_inner_sqrt, n) = max_func_applications(inner_goal, Func::SquareRoot, false);
-            let max_outer_sqrt =
-                max_func_applications(n, Func::SquareRoot, true).0 - max_inner_sqrt;
-            let max_sqrt = (max_inner_sqrt + max_outer_sqrt).min(30);
-            (1..max_sqrt)
-                .rev()
-                .flat_map(|sqrt_count| {
-                    let power_of_2 = POWER_OF_2[sqrt_count];
-                    // let inner_sqrt = max_inner_sqrt.min(sqrt_count);
-                    // let outer_sqrt = sqrt_count - inner_sqrt;
-                    let outer_sqrt = max_outer_sqrt.min(sqrt_count);
-                    let inner_sqrt = sqrt_count - outer_sqrt;
-                    let joiner_solutions = power_joiners
-                        .iter_mut()
-                        .flat_map(|joiner| {
-                            joiner
-                                .solve(
-                                    power_nums,
-                                    power_of_2,
-                                    SQUARES_DEPTH,
-                                    AtomFilter::None,
-                                    memo,
-                                )
-                                .into_iter()
-                                .collect_vec()
-                        })
-                        .take(3)
-                        .collect_vec();
-                    joiner_solutions
-                        .iter()
-                        .cloned()
-                        .filter_map(|(_power_score, power_atom)| {
-                            let mut goal_atom_inner = goal_atom_inner.clone();
-                            for _ in 0..inner_sqrt {
-                                goal_atom_inner.funcs.push(Func::SquareRoot);
-                            }
-                            let mut inner_goal_atom =
-                                Atom::new_express(goal_atom_inner, power_atom, Operation::Power);
-                            for _ in 0..outer_sqrt {
-                                inner_goal_atom.funcs.push(Func::SquareRoot);
-                            }
-                            let mut atom = goal_atom_outer.clone();
-                            atom.fill_hole(inner_goal_atom);
-
-                            let score = atom.get_score();
-                            if let AtomFilter::MinScore(min_score) = atom_filter {
-                                if score.score() <= min_score {
-                                    return None;
-                                }
-                            }
-                            if !atom.test(goal) {
-                                return None;
-                            }
-                            if let AtomFilter::MinScore(min_score) = &mut atom_filter {
-                                *min_score = score.score();
-                            }
-                            Some((score, atom))
-                        })
-                        .collect_vec()
-                })
-                .collect_vec()
+// depths for the anytime mode to search one after another, ending with the full depth
+fn search_depths(options: &SolveOptions) -> Vec<Depth> {
+    let depth = options.search.depth;
+    (0..=depth.num.max(depth.goal))
+        .map(|level| Depth {
+            num: level.min(depth.num),
+            goal: level.min(depth.goal),
         })
+        .collect_vec()
 }
 
-pub fn max_func_applications(mut num: f64, func: Func, rev: bool) -> (usize, f64) {
-    let mut count = 0;
-    while count < 100 {
-        let new_num = func.apply_rev_if(num, rev);
-        match new_num {
-            Some(new_num) => num = new_num,
-            None => return (count, num),
-        }
-        count += 1;
-    }
-    (count, num)
+// more numbers score higher, so those shapes are tried first
+fn solve_all(
+    nums: &[(u8, f64)],
+    goal: f64,
+    depth: Depth,
+    options: &SolveOptions,
+    memo: &mut Memo,
+    callback: impl Fn(Score, &Atom),
+    best_score: &mut u8,
+) -> Result<(), SolveError> {
+    solve_all_with_meeting(
+        nums,
+        goal,
+        depth,
+        options,
+        memo,
+        |score, atom, _| callback(score, atom),
+        best_score,
+    )
 }
 
-pub fn combinations_when_split<'a>(
-    nums: &'a [(u8, f64)],
-    split: usize,
-) -> impl Iterator<Item = (Vec<(u8, f64)>, Vec<(u8, f64)>)> + 'a {
-    fn rec(len: usize, split: usize) -> impl Iterator<Item = Vec<usize>> {
-        if split == 1 {
-            return (0..len).map(|x| vec![x]).collect_vec().into_iter();
-        }
-        let mut all_chosen: Vec<Vec<usize>> = Vec::new();
-        for i in 0..(len - split + 1) {
-            all_chosen.extend(rec(len - i - 1, split - 1).map(|x| {
-                std::iter::once(i)
-                    .chain(x.iter().map(|y| y + i + 1))
-                    .collect_vec()
-            }));
+fn solve_all_with_meeting(
+    nums: &[(u8, f64)],
+    goal: f64,
+    depth: Depth,
+    options: &SolveOptions,
+    memo: &mut Memo,
+    callback: impl Fn(Score, &Atom, f64),
+    best_score: &mut u8,
+) -> Result<(), SolveError> {
+    for num_count in (1..=nums.len()).rev() {
+        let _timing = stats::time_num_count(num_count);
+        let mut joiners = get_joiners(num_count)?
+            .into_iter()
+            .enumerate()
+            .collect_vec();
+        // an anytime level should give its first solutions early, so its cheaper
+        // shapes go first. the full search ends up with the same best score either way
+        if options.anytime {
+            joiners.sort_by_key(|(_, joiner)| joiner.estimated_cost());
         }
-        all_chosen.into_iter()
-    }
-    rec(nums.len(), split).map(move |chosen| {
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-        for (i, num) in nums.iter().enumerate() {
-            if chosen.contains(&i) {
-                left.push(*num);
-            } else {
-                right.push(*num);
+        for (shape, mut joiner) in joiners {
+            let _span = debug_span!("joiner", nums = num_count, shape).entered();
+            // reported by solve_warm, a stop only cuts off this run
+            if options.stop.is_stopped() {
+                return Ok(());
+            }
+            for (score, atom, meeting) in joiner.solve_with_meeting(
+                nums,
+                goal,
+                depth,
+                options.rules,
+                AtomFilter::MinScore(*best_score),
+                memo,
+            ) {
+                debug!(score = score.score(), %atom, "found a better solution");
+                *best_score = score.score();
+                callback(score, &atom, meeting);
             }
+            debug!(memo = memo.len(), "joined");
         }
-        (left, right)
-    })
+    }
+    Ok(())
 }
@@ -1,9 +1,12 @@
 use itertools::Itertools;
 
 use super::math::within_error;
+use super::program::Program;
+use super::rules::Rules;
 use super::{func_list::FuncList, score::Score};
 use core::panic;
 use std::fmt::{Display, Formatter};
+use tracing::trace;
 
 use super::operation::Operation;
 
@@ -23,6 +26,12 @@ impl From<Val> for Atom {
     }
 }
 
+impl From<f64> for Atom {
+    fn from(n: f64) -> Self {
+        Atom::new(n)
+    }
+}
+
 impl From<f64> for Val {
     fn from(n: f64) -> Self {
         Val::Num(n)
@@ -76,45 +85,89 @@ impl Atom {
             .iter()
             .fold(num, |acc, func| func.apply_if_limit(acc?, limit))
     }
-    pub fn eval_verbose(&self) -> Option<f64> {
+    pub fn value(&self) -> Option<f64> {
+        self.eval(false)
+    }
+    // every number used, sorted
+    pub fn nums(&self) -> Vec<f64> {
+        let mut nums = match &self.val {
+            Val::Num(n) => vec![*n],
+            Val::Express { left, right, .. } => {
+                let mut nums = left.nums();
+                nums.extend(right.nums());
+                nums
+            }
+            Val::Hole => Vec::new(),
+        };
+        nums.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        nums
+    }
+    // the same expression with every number replaced by a hole
+    pub fn with_holes(&self) -> Atom {
+        let mut atom = match &self.val {
+            Val::Num(_) | Val::Hole => Atom::new_hole(),
+            Val::Express { left, right, op } => {
+                Atom::new_express(left.with_holes(), right.with_holes(), op.clone())
+            }
+        };
+        atom.funcs = self.funcs;
+        atom
+    }
+    // each step of evaluating the atom, e.g. "2 + 3 = 5", up to the first one
+    // that fails
+    pub fn eval_steps(&self) -> Vec<String> {
+        let mut steps = Vec::new();
+        self.eval_steps_into(&mut steps);
+        steps
+    }
+    fn eval_steps_into(&self, steps: &mut Vec<String>) -> Option<f64> {
         let num = match &self.val {
             Val::Num(n) => Some(*n),
             Val::Express { left, right, op } => {
-                let left = left.eval_verbose()?;
-                let right = right.eval_verbose()?;
+                let left = left.eval_steps_into(steps)?;
+                let right = right.eval_steps_into(steps)?;
                 let res = op.apply_if_limit(left, right, false);
-                if op.is_switched() {
-                    println!("{} {} {} = {}", right, op, left, res.unwrap_or(f64::NAN));
+                let (first, second) = if op.is_switched() {
+                    (right, left)
                 } else {
-                    println!("{} {} {} = {}", left, op, right, res.unwrap_or(f64::NAN));
-                }
+                    (left, right)
+                };
+                steps.push(format!(
+                    "{} {} {} = {}",
+                    first,
+                    op,
+                    second,
+                    res.unwrap_or(f64::NAN)
+                ));
                 res
             }
             Val::Hole => panic!("eval with hole"),
         };
         self.funcs.iter().fold(num, |acc, func| {
             func.apply_if_limit(acc?, false).and_then(|res| {
-                println!("{}({}) = {}", func, acc?, res);
+                steps.push(format!("{}({}) = {}", func, acc?, res));
                 Some(res)
             })
         })
     }
 
     pub fn test(&self, goal: f64) -> bool {
-        if !within_error(self.eval(true).unwrap_or(f64::NAN), goal) {
-            return false;
-        }
-        if !self.all_funcs_necessary(goal) {
+        within_error(self.eval(true).unwrap_or(f64::NAN), goal)
+            && self
+                .possible_vals_with_removed_funcs()
+                .iter()
+                .rev()
+                .skip(1) // skip the last one because it's the original atom
+                .all(|x| !within_error(*x, goal))
+    }
+    // the same as test, compiling the atom first. compiling costs more than the
+    // walk saves for a single check, so it's kept to check and benchmark against
+    pub fn test_by_program(&self, goal: f64) -> bool {
+        let program = Program::from_atom(self);
+        if !within_error(program.eval(true).unwrap_or(f64::NAN), goal) {
             return false;
         }
-        true
-    }
-    fn all_funcs_necessary(&self, goal: f64) -> bool {
-        self.possible_vals_with_removed_funcs()
-            .iter()
-            .rev()
-            .skip(1) // skip the last one because it's the original atom
-            .all(|x| !within_error(*x, goal))
+        program.all_funcs_necessary(goal)
     }
     fn possible_vals_with_removed_funcs(&self) -> Vec<f64> {
         let possible_num = match &self.val {
@@ -123,13 +176,13 @@ impl Atom {
                 let left = left.possible_vals_with_removed_funcs();
                 let right = right.possible_vals_with_removed_funcs();
                 left.into_iter()
-                    .cartesian_product(right.into_iter())
+                    .cartesian_product(right)
                     .filter_map(|(l, r)| op.apply_no_limit(l, r))
                     .collect()
             }
             Val::Hole => panic!("eval with hole"),
         };
-        if self.funcs.len() == 0 {
+        if self.funcs.is_empty() {
             return possible_num;
         }
         self.funcs
@@ -138,16 +191,19 @@ impl Atom {
             .into_iter()
             .map(|(func, group)| (0..=group.count()).map(move |i| (func.clone(), i)))
             .multi_cartesian_product()
-            .map(|product| {
+            .flat_map(|product| {
                 possible_num.iter().filter_map(move |num| {
-                    product.iter().fold(Some(*num), |num, (func, func_repeat)| {
-                        (0..*func_repeat).fold(num, |num, _| func.apply_no_limit(num?))
+                    product.iter().try_fold(*num, |num, (func, func_repeat)| {
+                        (0..*func_repeat).try_fold(num, |num, _| func.apply_no_limit(num))
                     })
                 })
             })
-            .flatten()
             .collect()
     }
+    // like test, but also rejects operations the rules turn off
+    pub fn verify(&self, goal: f64, rules: &Rules) -> bool {
+        rules.allows_atom(self) && self.test(goal)
+    }
     pub fn fill_hole(&mut self, atom: Atom) {
         match &mut self.val {
             Val::Hole => {
@@ -171,7 +227,7 @@ impl Atom {
     pub fn get_steps_with_eval(&self) -> Vec<(f64, AtomStep)> {
         let mut steps = Vec::new();
         fn rec(atom: &Atom, i: &mut usize, steps: &mut Vec<(f64, AtomStep)>) -> Option<f64> {
-            let atom_step = i.clone();
+            let atom_step = *i;
             *i += 1;
             let mut num = match &atom.val {
                 Val::Num(n) => Some(*n),
@@ -199,7 +255,7 @@ impl Atom {
             }
             _ => panic!("score with hole"),
         };
-        score.add_funcs_list(self.funcs.clone()).resolve()
+        score.add_funcs_list(self.funcs).resolve()
     }
     pub fn split(mut self, step: AtomStep) -> (Atom, Atom) {
         let mut inner_atom = None;
@@ -213,10 +269,7 @@ impl Atom {
                 return;
             }
             let (atom_step, func_step) = target_step;
-            // println!("step {:?}", target_step);
-            // println!("i {}", *i);
-            // println!("funcs {}", atom.funcs.len());
-            // println!("atom: {}", atom);
+            trace!(step = ?target_step, i = *i, funcs = atom.funcs.len(), %atom, "split");
             if *i == atom_step {
                 let mut inner_funcs = FuncList::new();
                 for j in 0..func_step {
@@ -272,7 +325,7 @@ impl Display for Atom {
         }
         if let Val::Express { .. } = self.val {
             write!(f, "(")?;
-            end_str.insert_str(0, ")");
+            end_str.insert(0, ')');
         }
 
         match &self.val {
@@ -0,0 +1,110 @@
+use std::fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// limits that can cut off part of the search. the tree shapes aren't one of
+// them, because the joiners cover every binary tree for each number count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Bound {
+    // function chains longer than the depth weren't expanded
+    Depth,
+    // same, but for the smaller depth of the squares trick
+    SquaresDepth,
+    // only the first few power of 2 expressions were tried for each square
+    SquaresTake,
+    // more square roots could have been taken than the cap allows
+    SquareRootCap,
+    // values over the maximum number size were thrown away
+    NumSize,
+    // the search was stopped before it finished, see finder::stop
+    Stopped,
+    // functions that gave a non-integer value weren't followed
+    NonInteger,
+    // the squares trick only tries one or two numbers as the power and the goal
+    // side's largest step as the base, not every expression of that form
+    SquaresShapes,
+}
+
+impl Bound {
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bound::Depth => write!(f, "function depth"),
+            Bound::SquaresDepth => write!(f, "squares function depth"),
+            Bound::SquaresTake => write!(f, "power of 2 expressions per square"),
+            Bound::SquareRootCap => write!(f, "square root cap"),
+            Bound::NumSize => write!(f, "maximum number size"),
+            Bound::Stopped => write!(f, "search stopped early"),
+            Bound::NonInteger => write!(f, "non-integer function results"),
+            Bound::SquaresShapes => write!(f, "squares trick shapes"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BoundSet {
+    data: u8,
+}
+
+impl BoundSet {
+    pub fn new() -> Self {
+        Self { data: 0 }
+    }
+    pub fn insert(&mut self, bound: Bound) {
+        self.data |= bound.bit();
+    }
+    pub fn contains(&self, bound: Bound) -> bool {
+        self.data & bound.bit() != 0
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data == 0
+    }
+    pub fn iter(&self) -> impl Iterator<Item = Bound> + '_ {
+        Bound::iter().filter(|bound| self.contains(*bound))
+    }
+    pub fn extend(&mut self, other: BoundSet) {
+        self.data |= other.data;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Completeness {
+    pub bounds_hit: BoundSet,
+    pub best_score: u8,
+}
+
+impl Completeness {
+    // if no bound cut anything off, every expression was searched, so nothing
+    // can beat the best score under the current rules. every shortcut the
+    // search takes has to be a bound for this to hold
+    pub fn is_exact(&self) -> bool {
+        self.bounds_hit.is_empty()
+    }
+}
+
+impl Display for Completeness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_exact() {
+            return write!(
+                f,
+                "best score {} is optimal under the current rules",
+                self.best_score
+            );
+        }
+        write!(
+            f,
+            "best score {} is only a lower bound, limits hit: {}",
+            self.best_score,
+            self.bounds_hit
+                .iter()
+                .map(|bound| bound.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
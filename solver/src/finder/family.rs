@@ -0,0 +1,84 @@
+use super::atom::Atom;
+use super::math::MAX_NUM_SIZE;
+use super::rules::FamilyRanking;
+use super::score::Score;
+use std::fmt::{Display, Formatter};
+
+// a squares solution that only stopped at the square root cap. doubling the
+// power of 2 lets one more square root be taken, so the instance that was
+// built is just one member of a family that keeps going until the power of 2
+// no longer fits in the maximum number size
+#[derive(Debug, Clone)]
+pub struct Family {
+    // the capped instance that was built and tested
+    pub score: Score,
+    pub atom: Atom,
+    pub square_roots: usize,
+    // the largest instance that could be built and passed the same test
+    pub largest_square_roots: usize,
+    pub largest_score: u8,
+    pub largest_atom: Atom,
+}
+
+impl Family {
+    // build gives the instance with that many square roots, already tested, or
+    // None if it can't be built. counts are tried from the most down, stopping
+    // at the first that checks out. whether a power of 2 can be built doesn't
+    // follow from a larger or smaller one being built, so they can't be binary
+    // searched. returns None if no larger instance raises the score
+    pub fn detect(
+        score: Score,
+        atom: &Atom,
+        square_roots: usize,
+        mut build: impl FnMut(usize) -> Option<Atom>,
+    ) -> Option<Self> {
+        let (largest_square_roots, largest_atom) = (square_roots + 1..=largest_square_roots())
+            .rev()
+            .find_map(|count| build(count).map(|atom| (count, atom)))?;
+        let largest_score = largest_atom.get_score().score();
+        if largest_score <= score.score() {
+            return None;
+        }
+        Some(Self {
+            score,
+            atom: atom.clone(),
+            square_roots,
+            largest_square_roots,
+            largest_score,
+            largest_atom,
+        })
+    }
+    // how much each extra square root, from doubling the power of 2, adds to
+    // the score on average between the found and largest instances
+    pub fn score_step(&self) -> f64 {
+        (self.largest_score - self.score.score()) as f64
+            / (self.largest_square_roots - self.square_roots) as f64
+    }
+    pub fn rank(&self, ranking: FamilyRanking) -> u8 {
+        match ranking {
+            FamilyRanking::Found => self.score.score(),
+            FamilyRanking::Largest => self.largest_score,
+        }
+    }
+}
+
+// the most square roots whose power of 2 is still within the maximum number size
+fn largest_square_roots() -> usize {
+    MAX_NUM_SIZE.log2().floor() as usize
+}
+
+impl Display for Family {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "score grows by {} per doubling of the power of 2, largest instance within the maximum number size scores {} with {} square roots: {}, found instance with {} square roots scores {}: {}",
+            (self.score_step() * 100.).round() / 100.,
+            self.largest_score,
+            self.largest_square_roots,
+            self.largest_atom,
+            self.square_roots,
+            self.score,
+            self.atom
+        )
+    }
+}
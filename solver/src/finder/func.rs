@@ -1,10 +1,13 @@
 use crate::finder::math::{
     factorial, factorial_rev, square_root, square_root_rev, summation, summation_rev, within_limit,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, EnumString};
 
-#[derive(Debug, Clone, EnumIter, PartialEq)]
+#[derive(Debug, Clone, EnumIter, EnumString, PartialEq, Serialize, Deserialize)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
 pub enum Func {
     SquareRoot,
     Factorial,
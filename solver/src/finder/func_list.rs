@@ -7,6 +7,9 @@ use std::iter::Iterator;
 // 10 = Factorial
 // 11 = Summation
 
+// two bits for each function in the u64
+pub const CAPACITY: usize = 32;
+
 #[derive(Debug, Clone, Copy)]
 pub struct FuncList {
     data: u64,
@@ -20,7 +23,15 @@ impl FuncList {
     pub fn len(&self) -> usize {
         self.len
     }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
     pub fn push(&mut self, func: Func) {
+        assert!(
+            self.len < CAPACITY,
+            "a FuncList only fits {} functions",
+            CAPACITY
+        );
         match func {
             Func::SquareRoot => {
                 self.set_bits(self.len, false, true);
@@ -81,7 +92,7 @@ impl FuncList {
         }
         new
     }
-    pub fn iter(&self) -> FuncListIter {
+    pub fn iter(&self) -> FuncListIter<'_> {
         FuncListIter {
             list: self,
             index: 0,
@@ -95,6 +106,12 @@ impl FuncList {
     }
 }
 
+impl Default for FuncList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Iterator for FuncList {
     type Item = Func;
     fn next(&mut self) -> Option<Self::Item> {
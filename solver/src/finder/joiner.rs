@@ -1,11 +1,18 @@
+use crate::error::SolveError;
 use ahash::AHashMap as HashMap;
 use ordered_float::OrderedFloat;
 // use rustc_hash::FxHashMap as HashMap;
 
 use itertools::Itertools;
+use tracing::{trace, trace_span};
 
 use super::atom::Atom;
+use super::bounds::BoundSet;
+use super::options::Depth;
+use super::rules::Rules;
 use super::score::Score;
+use super::stats;
+use super::stop;
 use super::tree::{expand_funcs, Arena, Kind, Link, Path, Val};
 
 use super::tree_shapes::*;
@@ -19,44 +26,69 @@ pub enum AtomFilter {
     MinScore(u8),
 }
 impl Joiner {
-    fn from_strings(up: &str, down: &str) -> Self {
-        let up = Arena::from_string(up);
-        let down = Arena::from_string(down);
-        Self { up, down }
+    fn from_strings(up: &str, down: &str) -> Result<Self, SolveError> {
+        let up = Arena::from_string(up)?;
+        let down = Arena::from_string(down)?;
+        Ok(Self { up, down })
+    }
+    // a cheap guess at how long a solve takes: the goal side is searched again
+    // for every goal, while the number side mostly comes from the memo
+    pub fn estimated_cost(&self) -> (usize, usize) {
+        (self.down.len(), self.up.len())
     }
     pub fn solve<'a>(
         &'a mut self,
         nums: &[(u8, f64)],
         goal: f64,
-        depth: usize,
-        mut atom_filter: AtomFilter,
+        depth: Depth,
+        rules: Rules,
+        atom_filter: AtomFilter,
         memo: &'a mut Memo,
     ) -> impl Iterator<Item = (Score, Atom)> + 'a {
+        self.solve_with_meeting(nums, goal, depth, rules, atom_filter, memo)
+            .map(|(score, atom, _)| (score, atom))
+    }
+    // also gives the value the up and down sides meet at
+    pub fn solve_with_meeting<'a>(
+        &'a mut self,
+        nums: &[(u8, f64)],
+        goal: f64,
+        depth: Depth,
+        rules: Rules,
+        mut atom_filter: AtomFilter,
+        memo: &'a mut Memo,
+    ) -> impl Iterator<Item = (Score, Atom, f64)> + 'a {
         let up_perm_map = self.up.perm_map();
         let down_perm_map = self.down.perm_map();
         let perm_middle = up_perm_map.len();
         let perm_map = [&up_perm_map[..], &down_perm_map[..]].concat();
 
-        set_nums_and_goal_in_memo(nums, goal, depth, memo);
+        set_nums_and_goal_in_memo(nums, goal, depth, rules, memo);
+        stats::record(|stats| stats.joiners += 1);
 
         get_perms(nums, &perm_map)
             .into_iter()
+            .take_while(|_| !stop::watched_is_stopped())
             .flat_map(move |perm| {
-                self.up.populate(&perm[..perm_middle], None, memo);
-                self.up.solve(depth, memo);
+                let _span = trace_span!("permutation", ?perm).entered();
+                self.up.populate(&perm[..perm_middle], None);
+                self.up.solve(depth, rules, memo);
 
-                self.down.populate(&perm[perm_middle..], Some(goal), memo);
-                self.down.solve(depth, memo);
+                self.down.populate(&perm[perm_middle..], Some(goal));
+                self.down.solve(depth, rules, memo);
 
-                find_val_intersects(&self.up.keys[0], &self.down.keys[0], &memo)
+                let (mut intersections, mut tested) = (0, 0);
+                let found = find_val_intersects(&self.up.keys[0], &self.down.keys[0], memo)
                     .filter_map(|(up_val, down_val)| {
+                        intersections += 1;
                         let score = (up_val.score + down_val.score).resolve();
                         if let AtomFilter::MinScore(min_score) = atom_filter {
                             if score.score() <= min_score {
                                 return None;
                             }
                         }
-                        let atom = join_vals(&up_val, &self.up, &down_val, &self.down, &memo);
+                        let atom = join_vals(&up_val, &self.up, &down_val, &self.down, memo);
+                        tested += 1;
                         if !atom.test(goal) {
                             return None;
                         }
@@ -64,27 +96,59 @@ impl Joiner {
                             *min_score = score.score();
                         }
 
-                        Some((score, atom))
+                        Some((score, atom, up_val.num))
                     })
-                    .collect_vec()
+                    .collect_vec();
+                trace!(intersections, found = found.len(), "joined the sides");
+                stats::record(|stats| {
+                    stats.permutations += 1;
+                    stats.intersections += intersections;
+                    stats.tested += tested;
+                    stats.accepted += found.len() as u64;
+                    stats.peak_memo = stats.peak_memo.max(memo.len());
+                });
+                found
             })
     }
+    // the values the up side already reached for each permutation in the memo,
+    // each with a way to build its atom. nothing is searched, and keys already
+    // seen, e.g. by another joiner, are skipped
+    pub fn visit_up_vals(
+        &mut self,
+        nums: &[(u8, f64)],
+        memo: &Memo,
+        seen: &mut std::collections::HashSet<String>,
+        mut visit: impl FnMut(&Val, &dyn Fn() -> Atom),
+    ) {
+        let up_perm_map = self.up.perm_map();
+        let perm_middle = up_perm_map.len();
+        let perm_map = [&up_perm_map[..], &self.down.perm_map()[..]].concat();
+        for perm in get_perms(nums, &perm_map) {
+            self.up.populate(&perm[..perm_middle], None);
+            if !seen.insert(self.up.keys[0].clone()) {
+                continue;
+            }
+            let Some(vals) = memo.get(&self.up.keys[0]) else {
+                continue;
+            };
+            for val in vals {
+                visit(val, &|| val_to_atom(val, 0, &self.up, memo));
+            }
+        }
+    }
 }
 
 fn join_vals(up_val: &Val, up: &Arena, down_val: &Val, down: &Arena, memo: &Memo) -> Atom {
     let sub_atom = val_to_atom(up_val, 0, up, memo);
-    // println!("sub_atom: {}\n", sub_atom);
+    trace!(%sub_atom, "built the up side");
     let mut atom = val_to_atom_rev(down_val, 0, down, memo);
     atom.fill_hole(sub_atom);
-    // println!("atom: {}\n", atom);
+    trace!(%atom, "filled the down side");
     atom
 }
 fn val_to_atom(val: &Val, id: usize, arena: &Arena, memo: &Memo) -> Atom {
     let node = arena.get(id);
-    // println!(
-    //     "val_to_atom: ({} {} -> {}) {:?} {:?} {:?}",
-    //     id, val.origin, val.num, node.link, node.kind, val.path
-    // );
+    trace!(id, origin = val.origin, num = val.num, link = ?node.link, kind = ?node.kind, path = ?val.path, "val_to_atom");
     let mut atom = match &val.path {
         Path::Leaf => Atom::new(val.origin),
         Path::Combine { op, left, right } => {
@@ -104,7 +168,7 @@ fn val_to_atom(val: &Val, id: usize, arena: &Arena, memo: &Memo) -> Atom {
             Atom::new_express(left, right, op.clone())
         }
     };
-    atom.funcs = val.funcs.clone();
+    atom.funcs = val.funcs;
     atom
 }
 fn val_to_atom_rev(val: &Val, id: usize, arena: &Arena, memo: &Memo) -> Atom {
@@ -143,10 +207,7 @@ fn val_to_atom_rev(val: &Val, id: usize, arena: &Arena, memo: &Memo) -> Atom {
     let id_val_map = id_val_map.into_iter().flatten().collect::<Vec<_>>();
     fn rec(val: &Val, id: usize, arena: &Arena, id_val_map: &[Val], memo: &Memo) -> Atom {
         let node = arena.get(id);
-        // println!(
-        //     "rev: ({} {} -> {}) {:?} {:?} {:?}",
-        //     id, val.origin, val.num, node.link, node.kind, val.path
-        // );
+        trace!(id, origin = val.origin, num = val.num, link = ?node.link, kind = ?node.kind, path = ?val.path, "val_to_atom_rev");
         if matches!(node.kind, Kind::Num) {
             return val_to_atom(val, id, arena, memo);
         };
@@ -200,24 +261,32 @@ fn get_perms(nums: &[(u8, f64)], perm_map: &[bool]) -> Vec<Vec<(u8, f64)>> {
         .collect()
 }
 
-pub fn get_joiners(num_count: usize) -> Vec<Joiner> {
+pub fn get_joiners(num_count: usize) -> Result<Vec<Joiner>, SolveError> {
     match num_count {
         1 => TREE_1.iter(),
         2 => TREE_2.iter(),
         3 => TREE_3.iter(),
         4 => TREE_4.iter(),
         5 => TREE_5.iter(),
-        _ => panic!("unsupported tree"),
+        _ => return Err(SolveError::UnsupportedNumberCount(num_count)),
     }
     .map(|(up, down)| Joiner::from_strings(up, down))
     .collect()
 }
 
 use super::tree::{goal_key, num_key};
-fn set_nums_and_goal_in_memo(nums: &[(u8, f64)], goal: f64, depth: usize, memo: &mut Memo) {
+fn set_nums_and_goal_in_memo(
+    nums: &[(u8, f64)],
+    goal: f64,
+    depth: Depth,
+    rules: Rules,
+    memo: &mut Memo,
+) {
     for (tag, num) in nums {
         let origin_val = Val::new_pure_leaf(*num, true);
-        let num_vals = expand_funcs(*num, false, depth)
+        let (paths, bounds_hit) = expand_funcs(*num, false, depth.num, rules);
+        memo.bounds.extend(bounds_hit);
+        let num_vals = paths
             .into_iter()
             .map(|(num, funcs)| origin_val.clone_with_funcs(num, funcs))
             .chain(std::iter::once(origin_val.clone()))
@@ -225,7 +294,9 @@ fn set_nums_and_goal_in_memo(nums: &[(u8, f64)], goal: f64, depth: usize, memo:
         memo.insert(num_key(*num, *tag), num_vals);
     }
     let origin_val = Val::new_pure_leaf(goal, false);
-    let goal_vals = expand_funcs(goal, true, depth)
+    let (paths, bounds_hit) = expand_funcs(goal, true, depth.goal, rules);
+    memo.extend_goal_bounds(&goal_key(goal), bounds_hit);
+    let goal_vals = paths
         .into_iter()
         .map(|(num, funcs)| origin_val.clone_with_funcs(num, funcs))
         .chain(std::iter::once(origin_val.clone()))
@@ -254,26 +325,43 @@ fn find_val_intersects<'a>(
 
     let longer_val_map = memo.get_or_create_map(longer_key);
 
-    shorter_vals.iter().filter_map(move |shorter_val| {
-        longer_val_map
+    // every val with the value, not just one, since the others can score
+    // higher or pass where it fails
+    shorter_vals.iter().flat_map(move |shorter_val| {
+        let val_is = longer_val_map
             .get(&OrderedFloat(shorter_val.num))
-            .map(|val_i| {
-                let longer_val = &longer_vals[*val_i];
-                if switch {
-                    (longer_val.clone(), shorter_val.clone())
-                } else {
-                    (shorter_val.clone(), longer_val.clone())
-                }
-            })
+            .cloned()
+            .unwrap_or_default();
+        val_is.into_iter().map(move |val_i| {
+            let longer_val = &longer_vals[val_i];
+            if switch {
+                (longer_val.clone(), shorter_val.clone())
+            } else {
+                (shorter_val.clone(), longer_val.clone())
+            }
+        })
     })
 }
 
 use std::cell::RefCell;
 use std::rc::Rc;
-type ValMap = HashMap<OrderedFloat<f64>, usize>;
+// the indices of the vals with each value
+type ValMap = HashMap<OrderedFloat<f64>, Vec<usize>>;
+// a memo is only valid for the depth and rules it was filled with
 pub struct Memo {
     map: HashMap<String, Vec<Val>>,
     map_map: RefCell<HashMap<String, Rc<ValMap>>>,
+    // limits that cut off goal independent values while filling the memo
+    pub bounds: BoundSet,
+    // limits that cut off values worked back from a goal, by the goal's key, so
+    // a solve only reports its own goal's
+    goal_bounds: HashMap<String, BoundSet>,
+}
+
+impl Default for Memo {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Memo {
@@ -281,21 +369,30 @@ impl Memo {
         Self {
             map: HashMap::default(),
             map_map: RefCell::new(HashMap::default()),
+            bounds: BoundSet::new(),
+            goal_bounds: HashMap::default(),
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
     pub fn get(&self, key: &str) -> Option<&[Val]> {
         self.map.get(key).map(|vals| vals.as_slice())
     }
 
-    pub fn get_or_create_map<'a>(&self, key: &str) -> Rc<ValMap> {
+    pub fn get_or_create_map(&self, key: &str) -> Rc<ValMap> {
         if !self.map_map.borrow().contains_key(key) {
             let vals = self.map.get(key).expect("key not found");
-            let val_map = vals
-                .iter()
-                .enumerate()
-                .map(|(i, val)| (val.num.into(), i))
-                .collect::<ValMap>();
+            let mut val_map = ValMap::default();
+            for (i, val) in vals.iter().enumerate() {
+                val_map.entry(val.num.into()).or_default().push(i);
+            }
             self.map_map
                 .borrow_mut()
                 .insert(key.to_string(), Rc::new(val_map));
@@ -306,4 +403,41 @@ impl Memo {
     pub fn insert(&mut self, key: String, val: Vec<Val>) {
         self.map.insert(key, val);
     }
+
+    // the limits a search for the goal hit, the goal independent ones included
+    pub fn bounds_for(&self, goal: f64) -> BoundSet {
+        let mut bounds = self.bounds;
+        if let Some(goal_bounds) = self.goal_bounds.get(&goal_key(goal)) {
+            bounds.extend(*goal_bounds);
+        }
+        bounds
+    }
+    pub fn extend_goal_bounds(&mut self, goal_key: &str, bounds: BoundSet) {
+        if !bounds.is_empty() {
+            self.goal_bounds
+                .entry(goal_key.to_string())
+                .or_default()
+                .extend(bounds);
+        }
+    }
+
+    // frees every entry that depends on the goal
+    pub fn remove_goal(&mut self, goal: f64) {
+        let goal_key = goal_key(goal);
+        self.goal_bounds.remove(&goal_key);
+        self.map.retain(|key, _| !has_leaf(key, &goal_key));
+        self.map_map
+            .borrow_mut()
+            .retain(|key, _| !has_leaf(key, &goal_key));
+    }
+}
+
+// whether the leaf's key is one of the key's leaves, not just the start of
+// another one, like "G 1" in "G 10" or "G 1.5"
+fn has_leaf(key: &str, leaf_key: &str) -> bool {
+    key.match_indices(leaf_key).any(|(i, _)| {
+        let before = key[..i].chars().next_back();
+        let after = key[i + leaf_key.len()..].chars().next();
+        matches!(before, None | Some('(' | ' ')) && matches!(after, None | Some(')' | ' '))
+    })
 }
@@ -0,0 +1,191 @@
+use super::atom::{Atom, Val};
+use super::func::Func;
+use super::math::{within_error, DIVIDE_DELTA, POWER_DELTA};
+use super::operation::Operation;
+use std::fmt::{Display, Formatter};
+
+// evaluates an atom with plain javascript number semantics: Math.pow gives NaN
+// for odd roots of negative numbers and there's no maximum number size. this
+// is a guess at what the site does, not a port of its inputLogic.js, and it
+// disagrees with finder::math, which is. so solutions and attempts are only
+// accepted by finder::math, and this just warns about attempts it would reject
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsReject {
+    // NaN, e.g. the square root of a negative number
+    NotANumber,
+    // Infinity, e.g. dividing by 0
+    Infinite,
+    // a division or power so close to 0 the game throws it away
+    NearZero(f64),
+    // a power so close to 1 the game throws it away
+    NearOne(f64),
+    // factorials and summations only take whole numbers
+    NotWhole(f64),
+    // the result isn't the goal
+    WrongResult(f64),
+}
+
+impl Display for JsReject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsReject::NotANumber => write!(f, "a step gives NaN"),
+            JsReject::Infinite => write!(f, "a step gives Infinity"),
+            JsReject::NearZero(num) => write!(f, "{} is too close to 0", to_js_string(*num)),
+            JsReject::NearOne(num) => write!(f, "{} is too close to 1", to_js_string(*num)),
+            JsReject::NotWhole(num) => write!(f, "{} isn't a whole number", to_js_string(*num)),
+            JsReject::WrongResult(num) => write!(f, "the result is {}", to_js_string(*num)),
+        }
+    }
+}
+
+pub fn js_check(atom: &Atom, goal: f64) -> Result<(), JsReject> {
+    let res = js_eval(atom)?;
+    if !within_error(res, goal) {
+        return Err(JsReject::WrongResult(res));
+    }
+    Ok(())
+}
+
+pub fn js_eval(atom: &Atom) -> Result<f64, JsReject> {
+    let mut num = match &atom.val {
+        Val::Num(n) => *n,
+        Val::Express { left, right, op } => js_operation(op, js_eval(left)?, js_eval(right)?)?,
+        Val::Hole => panic!("eval with hole"),
+    };
+    for func in atom.funcs.iter() {
+        num = js_func(&func, num)?;
+    }
+    Ok(num)
+}
+
+fn js_operation(op: &Operation, left: f64, right: f64) -> Result<f64, JsReject> {
+    match op {
+        Operation::Add => finite(left + right),
+        Operation::Subtract => finite(left - right),
+        Operation::SubtractSwitch => finite(right - left),
+        Operation::Multiply => finite(left * right),
+        Operation::Divide => js_divide(left, right),
+        Operation::DivideSwitch => js_divide(right, left),
+        Operation::Power | Operation::PowerNeg => js_power(left, right),
+        Operation::PowerSwitch | Operation::PowerNegSwitch => js_power(right, left),
+        Operation::Root => js_root(left, right),
+        Operation::RootSwitch => js_root(right, left),
+    }
+}
+
+fn js_func(func: &Func, num: f64) -> Result<f64, JsReject> {
+    match func {
+        Func::SquareRoot => {
+            let res = finite(num.sqrt())?;
+            reject_near_zero_or_one(num, 0.5, res)
+        }
+        Func::Factorial => {
+            let n = whole(num)?;
+            // a loop like the game's, which can overflow to Infinity
+            finite((2..=n as u64).fold(1., |acc, i| acc * i as f64))
+        }
+        Func::Summation => {
+            let n = whole(num)?;
+            finite(n * (n + 1.) / 2.)
+        }
+    }
+}
+
+fn js_divide(left: f64, right: f64) -> Result<f64, JsReject> {
+    let res = finite(left / right)?;
+    if res.abs() <= DIVIDE_DELTA && left != 0. {
+        return Err(JsReject::NearZero(res));
+    }
+    Ok(res)
+}
+
+fn js_power(left: f64, right: f64) -> Result<f64, JsReject> {
+    if left == 0. && right == 0. {
+        return Err(JsReject::NotANumber);
+    }
+    let res = finite(math_pow(left, right))?;
+    reject_near_zero_or_one(left, right, res)
+}
+
+fn js_root(left: f64, right: f64) -> Result<f64, JsReject> {
+    let exponent = finite(1. / left)?;
+    if exponent.abs() <= POWER_DELTA {
+        return Err(JsReject::NearZero(exponent));
+    }
+    js_power(right, exponent)
+}
+
+fn reject_near_zero_or_one(left: f64, right: f64, res: f64) -> Result<f64, JsReject> {
+    if (res - 1.).abs() <= POWER_DELTA && left.abs() != 1. && right != 0. {
+        return Err(JsReject::NearOne(res));
+    }
+    if res.abs() <= POWER_DELTA && left != 0. {
+        return Err(JsReject::NearZero(res));
+    }
+    Ok(res)
+}
+
+// Math.pow, which differs from powf when raising 1 or -1 to NaN or Infinity
+fn math_pow(left: f64, right: f64) -> f64 {
+    if right.is_nan() || (left.abs() == 1. && right.is_infinite()) {
+        return f64::NAN;
+    }
+    left.powf(right)
+}
+
+fn finite(num: f64) -> Result<f64, JsReject> {
+    if num.is_nan() {
+        Err(JsReject::NotANumber)
+    } else if num.is_infinite() {
+        Err(JsReject::Infinite)
+    } else {
+        Ok(num)
+    }
+}
+
+fn whole(num: f64) -> Result<f64, JsReject> {
+    if num < 0. || num.fract() != 0. {
+        return Err(JsReject::NotWhole(num));
+    }
+    Ok(num)
+}
+
+// Number.prototype.toString: the shortest digits that round trip, like rust,
+// but switching to exponent notation below 1e-6 and from 1e21 up
+pub fn to_js_string(num: f64) -> String {
+    if num.is_nan() {
+        return "NaN".to_string();
+    }
+    if num.is_infinite() {
+        return if num > 0. { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if num == 0. {
+        return "0".to_string();
+    }
+    if num < 0. {
+        return format!("-{}", to_js_string(-num));
+    }
+    // rust's exponent format already has the shortest digits
+    let formatted = format!("{:e}", num);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // the decimal point goes after the nth digit
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { "-" } else { "+" };
+        let mantissa = if k == 1 {
+            digits
+        } else {
+            format!("{}.{}", &digits[..1], &digits[1..])
+        };
+        format!("{}e{}{}", mantissa, sign, (n - 1).abs())
+    }
+}
@@ -1,26 +1,41 @@
 use itertools::iproduct;
 use itertools::repeat_n;
 use itertools::Itertools;
-use math::within_error;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::time::Instant;
 
 use strum::IntoEnumIterator;
 
-pub mod operation;
-use operation::Operation;
-pub mod func;
-use func::Func;
+use super::atom::Atom as SolvedAtom;
+use super::bounds::{Bound, BoundSet, Completeness};
+use super::func::Func;
+use super::math::within_error;
+use super::operation::Operation;
+use super::options::SolveOptions;
+use super::rules::Rules;
+use super::score::Score;
+use super::stats::SolveStats;
+use super::stop::Stop;
+use super::SolveReport;
+use crate::error::SolveError;
+use ordered_float::OrderedFloat;
+use tracing::{debug, info, info_span};
+
 pub mod atom;
-use atom::{Atom, FuncAtom};
+use atom::{is_power, Atom, AtomVal, FuncAtom};
 pub mod atom_store;
 use atom_store::{AtomGroup, AtomStore};
-pub mod math;
-use ordered_float::OrderedFloat;
-
 pub mod codon;
 
+// the engine from before the joiners: every expression of up to four of the
+// numbers is built up front, then for each score, from the lowest, functions
+// are spread over them until the value, or the last number and functions
+// applied to it, meets a path from the goal. it only reports plain solutions,
+// without strategies, families or near misses, and is kept to check the
+// joiners against
+
 const PARA: bool = true;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Copy)]
@@ -41,7 +56,7 @@ impl Used {
     }
 
     fn clone_set(&self, index: usize) -> Used {
-        let mut clone = self.clone();
+        let mut clone = *self;
         clone.set(index);
         clone
     }
@@ -52,6 +67,85 @@ impl Used {
         self.0 & other.0 != 0
     }
 }
+
+// like finder::solve, with the legacy engine. functions are spread over an
+// expression up to the larger of the search depths
+pub fn solve(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+    callback: impl Fn(Score, &SolvedAtom),
+) -> Result<SolveReport, SolveError> {
+    let _span = info_span!("solve", engine = "legacy", goal, ?nums).entered();
+    if nums.is_empty() || nums.len() > 5 {
+        return Err(SolveError::UnsupportedNumberCount(nums.len()));
+    }
+    let started = options.timed.then(Instant::now);
+    let rules = options.rules;
+    let max_funcs = options.search.depth.num.max(options.search.depth.goal) as u32;
+    info!("solving legacy");
+    let store = create_atom_store(nums, rules);
+    let goal_paths = create_goal_paths(goal, nums, rules);
+    debug!(
+        atoms = store.len(),
+        paths = goal_paths.len(),
+        "built the atoms"
+    );
+
+    let mut best_score = 0;
+    // more functions than that are never spread over an expression
+    let mut bounds_hit = BoundSet::new();
+    bounds_hit.insert(Bound::Depth);
+    let max_score = store
+        .iter()
+        .map(|(base_score, _)| *base_score)
+        .max()
+        .unwrap_or(0)
+        + max_funcs;
+    for min_score in 1..=max_score {
+        if options.stop.is_stopped() {
+            bounds_hit.insert(Bound::Stopped);
+            break;
+        }
+        let found = get_solution_with_score(
+            min_score,
+            max_funcs,
+            &goal_paths,
+            &store,
+            rules,
+            &options.stop,
+        );
+        let Some((func_atom, _)) = found else {
+            continue;
+        };
+        let atom = func_atom.to_atom();
+        let score = atom.get_score();
+        debug!(min_score, %atom, "found a solution");
+        if score.score() > best_score {
+            best_score = score.score();
+            callback(score, &atom);
+        }
+    }
+    Ok(SolveReport {
+        best_score,
+        near_miss: None,
+        completeness: Completeness {
+            bounds_hit,
+            best_score,
+        },
+        strategies: Vec::new(),
+        families: Vec::new(),
+        stats: SolveStats {
+            phases: started
+                .map(|started| ("legacy".to_string(), started.elapsed()))
+                .into_iter()
+                .collect(),
+            ..SolveStats::default()
+        },
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn solution_with_least_funcs(
     goal_paths: &GoalPaths,
     atom: &Atom,
@@ -59,15 +153,18 @@ pub fn solution_with_least_funcs(
     atom_group: &AtomGroup,
     codon_index: usize,
     codon_count: usize,
-    funcs: &Vec<Func>,
-    distribution: &Vec<usize>,
+    funcs: &[Func],
+    distribution: &[usize],
 ) -> Option<(FuncAtom, u32)> {
     // fast eval to check if path to goal exists
     let val = atom_group.eval_with_funcs(codon_index, codon_count, funcs, distribution, true)?;
-    let path = goal_paths.get_path(val, &used)?;
+    let path = goal_paths.get_path(val, used)?;
 
-    // add the funcs from the path to the funcs we already have
-    let min_funcs = funcs.len() - path.non_func_score_delta(used) as usize;
+    // add the funcs from the path to the funcs we already have. a path that
+    // scores more than the funcs on its own needs none of them kept
+    let min_funcs = funcs
+        .len()
+        .saturating_sub(path.non_func_score_delta(used) as usize);
     let (funcs, distribution, atom) = path.edit(funcs, distribution, atom);
     // create a bit mask for all possible combinations of funcs
     let bit_mask_range = 2u64.pow(distribution.len() as u32);
@@ -82,8 +179,7 @@ pub fn solution_with_least_funcs(
         }
         let no_limit_success = atom
             .eval_with_funcs(&new_funcs, &new_distribution, false)
-            .map(|test| within_error(test, goal_paths.goal))
-            .unwrap_or(false);
+            .is_some_and(|test| within_error(test, goal_paths.goal));
         if !no_limit_success {
             continue;
         }
@@ -96,8 +192,7 @@ pub fn solution_with_least_funcs(
         // check if solution works with limit
         let success = atom
             .eval_with_funcs(&new_funcs, &new_distribution, true)
-            .map(|test| within_error(test, goal_paths.goal))
-            .unwrap_or(false);
+            .is_some_and(|test| within_error(test, goal_paths.goal));
         if success {
             return Some((
                 FuncAtom::new(atom.clone(), &new_funcs, &new_distribution),
@@ -109,63 +204,70 @@ pub fn solution_with_least_funcs(
 }
 
 pub fn get_solution_in_group(
-    func_count: &u32,
+    func_count: u32,
     goal_paths: &GoalPaths,
     atom_group: &AtomGroup,
+    rules: Rules,
+    stop: &Stop,
 ) -> Option<(FuncAtom, u32)> {
-    fn find_map(
-        func_count: &u32,
-        goal_paths: &GoalPaths,
-        atom_group: &AtomGroup,
-        ((atom, used), (codon_index, codon_count)): (&(Atom, Used), &(usize, usize)),
-    ) -> Option<(FuncAtom, u32)> {
-        Func::iter()
-            .combinations_with_replacement(*func_count as usize)
-            .find_map(|funcs| {
-                (0..atom.count_func_atoms() as usize)
-                    .combinations_with_replacement(*func_count as usize)
-                    .find_map(|distribution| {
-                        solution_with_least_funcs(
-                            goal_paths,
-                            atom,
-                            used,
-                            atom_group,
-                            *codon_index,
-                            *codon_count,
-                            &funcs,
-                            &distribution,
-                        )
-                    })
-            })
-    }
+    let find_map =
+        |((atom, used), (codon_index, codon_count)): (&(Atom, Used), &(usize, usize))| {
+            // rayon's threads can't see the watched stop, so it's checked here
+            if stop.is_stopped() {
+                return None;
+            }
+            Func::iter()
+                .filter(|func| rules.allows_func(func))
+                .combinations_with_replacement(func_count as usize)
+                .find_map(|funcs| {
+                    (0..atom.count_func_atoms() as usize)
+                        .combinations_with_replacement(func_count as usize)
+                        .find_map(|distribution| {
+                            solution_with_least_funcs(
+                                goal_paths,
+                                atom,
+                                used,
+                                atom_group,
+                                *codon_index,
+                                *codon_count,
+                                &funcs,
+                                &distribution,
+                            )
+                        })
+                })
+        };
     if PARA {
-        atom_group
-            .par_iter()
-            .find_map_any(|atom_info| find_map(func_count, goal_paths, atom_group, atom_info))
+        atom_group.par_iter().find_map_any(find_map)
     } else {
-        atom_group
-            .iter()
-            .find_map(|atom_info| find_map(func_count, goal_paths, atom_group, atom_info))
+        atom_group.iter().find_map(find_map)
     }
 }
+
 pub fn get_solution_with_score(
     min_score: u32,
+    max_funcs: u32,
     goal_paths: &GoalPaths,
     store: &AtomStore,
+    rules: Rules,
+    stop: &Stop,
 ) -> Option<(FuncAtom, u32)> {
     store
         .iter()
-        .filter(|(base_score, _)| **base_score <= min_score)
+        .filter(|(base_score, _)| {
+            **base_score <= min_score && min_score - **base_score <= max_funcs
+        })
         .find_map(|(base_score, atom_group)| {
-            get_solution_in_group(&(min_score - base_score), goal_paths, atom_group)
+            get_solution_in_group(min_score - base_score, goal_paths, atom_group, rules, stop)
         })
 }
 
-pub fn create_atoms(nums: &[f64]) -> Vec<(Atom, Used)> {
+// every expression that leaves at least one number for the goal path
+pub fn create_atoms(nums: &[f64], rules: Rules) -> Vec<(Atom, Used)> {
     fn rec(
         nums: &[f64],
         used: Used,
         min_unused: usize,
+        rules: Rules,
         memo: &mut HashMap<(Used, usize), Vec<(Atom, Used)>>,
     ) -> Vec<(Atom, Used)> {
         // try getting from memo
@@ -191,19 +293,15 @@ pub fn create_atoms(nums: &[f64]) -> Vec<(Atom, Used)> {
         // express atoms
         // need at least 2 unused nums to make an express atom
         if available_count >= 2 {
-            for (left, left_used) in rec(nums, used, min_unused + 1, memo) {
-                for (right, right_used) in rec(nums, left_used, min_unused, memo) {
-                    for op in Operation::iter() {
+            for (left, left_used) in rec(nums, used, min_unused + 1, rules, memo) {
+                for (right, right_used) in rec(nums, left_used, min_unused, rules, memo) {
+                    // both orders of the sides are made, so switched operations
+                    // would only repeat them
+                    for op in Operation::iter().filter(|op| !op.is_switched() && rules.allows(op)) {
                         atoms.push((
-                            Atom::new_express(left.clone(), right.clone(), op.clone()),
+                            Atom::new_express(left.clone(), right.clone(), op),
                             right_used,
                         ));
-                        if !op.is_commutative() {
-                            atoms.push((
-                                Atom::new_express(right.clone(), left.clone(), op.clone()),
-                                right_used,
-                            ));
-                        }
                     }
                 }
             }
@@ -214,36 +312,31 @@ pub fn create_atoms(nums: &[f64]) -> Vec<(Atom, Used)> {
         atoms
     }
     let mut memo = HashMap::new();
-    let ret = rec(nums, Used::new(), 1, &mut memo)
+    rec(nums, Used::new(), 1, rules, &mut memo)
         .into_iter()
         .filter(|(atom, _)| atom.eval_possible())
-        .collect();
-    ret
+        .collect()
 }
 
-pub fn create_atom_store(nums: &[f64]) -> AtomStore {
-    AtomStore::new(create_atoms(nums))
+pub fn create_atom_store(nums: &[f64], rules: Rules) -> AtomStore {
+    AtomStore::new(create_atoms(nums, rules))
 }
 
-pub fn create_goal_paths(goal: f64, nums: &[f64]) -> GoalPaths {
-    GoalPaths::new(goal, nums)
+pub fn create_goal_paths(goal: f64, nums: &[f64], rules: Rules) -> GoalPaths {
+    GoalPaths::new(goal, nums, rules)
 }
 
-#[derive(Clone, Debug)]
-enum OperationSide {
-    Left,
-    Right,
-}
 #[derive(Clone, Debug)]
 enum GoalPath {
     Single {
         outer_funcs: Vec<Func>,
     },
+    // the last number on the left of the expression. switched operations put
+    // it on the right
     Double {
         outer_funcs: Vec<Func>,
         num_funcs: Vec<Func>,
         op: Operation,
-        side: OperationSide,
         used: Used,
         num: f64,
     },
@@ -253,7 +346,6 @@ impl GoalPath {
         outer_funcs: Vec<Func>,
         num_funcs: Vec<Func>,
         op: Operation,
-        side: OperationSide,
         used: Used,
         num: f64,
     ) -> GoalPath {
@@ -261,7 +353,6 @@ impl GoalPath {
             outer_funcs,
             num_funcs,
             op,
-            side,
             used,
             num,
         }
@@ -296,51 +387,30 @@ impl GoalPath {
                 outer_funcs,
                 num_funcs,
                 op,
-                side,
                 num,
                 ..
             } => {
-                match side {
-                    OperationSide::Left => {
-                        let new_atom =
-                            Atom::new_express::<Atom, &f64>(atom.clone(), num.into(), op.clone());
-                        // outer_funcs at start
-                        // atom funcs in middle
-                        // num_funcs at end
-                        let funcs = outer_funcs
-                            .iter()
-                            .chain(funcs.iter())
-                            .chain(num_funcs.iter())
-                            .cloned()
-                            .collect::<Vec<_>>();
-                        // we need to get the end_i for num_funcs distribution
-                        let end_i = new_atom.count_func_atoms() as usize - 1;
-                        let distribution = repeat_n(0usize, outer_funcs.len())
-                            .chain(distribution.iter().map(|i| i + 1))
-                            .chain(repeat_n(end_i, num_funcs.len()))
-                            .collect::<Vec<_>>();
-                        (funcs, distribution, new_atom)
-                    }
-                    OperationSide::Right => {
-                        let new_atom =
-                            Atom::new_express::<&f64, Atom>(num.into(), atom.clone(), op.clone());
-                        // outer_funcs at start
-                        // num_funcs in middle
-                        // atom funcs at end
-                        let funcs = outer_funcs
-                            .iter()
-                            .chain(num_funcs.iter())
-                            .chain(funcs.iter())
-                            .cloned()
-                            .collect::<Vec<_>>();
-                        // we know i will be 1 for num_funcs distribution
-                        let distribution = repeat_n(0usize, outer_funcs.len())
-                            .chain(repeat_n(1usize, num_funcs.len()))
-                            .chain(distribution.iter().map(|i| i + 2))
-                            .collect::<Vec<_>>();
-                        (funcs, distribution, new_atom)
-                    }
-                }
+                let new_atom = Atom::new_express::<&f64, Atom>(num, atom.clone(), op.clone());
+                // outer_funcs at start
+                // num_funcs in middle
+                // atom funcs at end
+                let funcs = outer_funcs
+                    .iter()
+                    .chain(num_funcs.iter())
+                    .chain(funcs.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                // immune nodes take no functions, so they have no index
+                let AtomVal::Express { left, .. } = &new_atom.val else {
+                    unreachable!("a double path makes an expression")
+                };
+                let num_i = usize::from(!new_atom.immune);
+                let atom_i = num_i + usize::from(!left.immune);
+                let distribution = repeat_n(0usize, outer_funcs.len())
+                    .chain(repeat_n(num_i, num_funcs.len()))
+                    .chain(distribution.iter().map(|i| i + atom_i))
+                    .collect::<Vec<_>>();
+                (funcs, distribution, new_atom)
             }
         }
     }
@@ -348,12 +418,7 @@ impl GoalPath {
         match self {
             GoalPath::Single { .. } => 0,
             GoalPath::Double { op, .. } => {
-                (if used.count() == 4 { 1 } else { 0 }
-                    + match op {
-                        Operation::Power | Operation::Root => 1,
-                        _ => 0,
-                    }
-                    + 1)
+                u32::from(used.count() == 4) + u32::from(is_power(op)) + 1
             }
         }
     }
@@ -366,13 +431,8 @@ impl GoalPath {
                 op,
                 ..
             } => {
-                outer_funcs.len() as u32
-                    + num_funcs.len() as u32
-                    + match op {
-                        Operation::Power | Operation::Root => 1,
-                        _ => 0,
-                    }
-                    + 1 // assume that there are 4 numbers
+                outer_funcs.len() as u32 + num_funcs.len() as u32 + u32::from(is_power(op)) + 1
+                // assume that there are 4 numbers
             }
         }
     }
@@ -387,51 +447,34 @@ impl Display for GoalPath {
                 outer_funcs,
                 num_funcs,
                 op,
-                side,
                 num,
                 ..
             } => {
                 let num_funcs = num_funcs.iter().join(" ");
                 let outer_funcs = outer_funcs.iter().rev().join(" ");
-                match side {
-                    OperationSide::Left => write!(
-                        f,
-                        "{} {} {}",
-                        format!("{} {}", num_funcs, num),
-                        op,
-                        outer_funcs,
-                    ),
-                    OperationSide::Right => write!(
-                        f,
-                        "{} {} {}",
-                        outer_funcs,
-                        op,
-                        format!("{} {}", num_funcs, num),
-                    ),
-                }
+                write!(f, "{} {} {} {}", outer_funcs, op, num_funcs, num)
             }
         }
     }
 }
-fn expand_funcs(start: f64, reverse: bool) -> Vec<(f64, Vec<Func>)> {
+fn expand_funcs(start: f64, reverse: bool, rules: Rules) -> Vec<(f64, Vec<Func>)> {
     let mut highest_level_paths: Vec<(f64, Vec<Func>)> = vec![(start, vec![])];
     let mut paths = highest_level_paths.clone();
-    while highest_level_paths.len() > 0 {
+    while !highest_level_paths.is_empty() {
         highest_level_paths = highest_level_paths
             .into_iter()
-            .map(|(n, funcs)| {
+            .flat_map(|(n, funcs)| {
                 Func::iter()
-                    .filter_map(|func| match func.apply_reverse_if(n, reverse) {
-                        Some(n) => {
+                    .filter(|func| rules.allows_func(func))
+                    .filter_map(|func| {
+                        func.apply_rev_if(n, reverse).map(|n| {
                             let mut new_funcs = funcs.clone();
                             new_funcs.push(func);
-                            Some((n, new_funcs))
-                        }
-                        None => None,
+                            (n, new_funcs)
+                        })
                     })
                     .collect::<Vec<(f64, Vec<Func>)>>()
             })
-            .flatten()
             .collect();
         paths.extend(highest_level_paths.clone());
     }
@@ -443,8 +486,8 @@ pub struct GoalPaths {
     paths: HashMap<OrderedFloat<f64>, GoalPath>,
 }
 impl GoalPaths {
-    fn new(goal: f64, nums: &[f64]) -> GoalPaths {
-        let single_paths = expand_funcs(goal, true);
+    fn new(goal: f64, nums: &[f64], rules: Rules) -> GoalPaths {
+        let single_paths = expand_funcs(goal, true, rules);
 
         let mut all_paths: Vec<(f64, GoalPath)> = single_paths
             .iter()
@@ -452,34 +495,19 @@ impl GoalPaths {
             .collect();
         for (i, og_num) in nums.iter().enumerate() {
             let used = Used::new_set(i);
-            let num_expand = expand_funcs(*og_num, false);
+            let num_expand = expand_funcs(*og_num, false, rules);
+            let ops = Operation::iter().filter(|op| rules.allows(op));
             for ((num, num_funcs), (outer, outer_funcs), op) in
-                iproduct!(num_expand.iter(), single_paths.iter(), Operation::iter())
+                iproduct!(num_expand.iter(), single_paths.iter(), ops)
             {
-                let right_path = op.apply_reverse_left(*num, *outer);
-                let left_path = op.apply_reverse_right(*num, *outer);
-                if let Some(right_path) = right_path {
+                if let Some(path) = op.apply_rev(*num, *outer) {
                     all_paths.push((
-                        right_path.into(),
+                        path,
                         GoalPath::new_double(
                             outer_funcs.clone(),
                             num_funcs.clone(),
                             op.clone(),
-                            OperationSide::Right,
-                            used.clone(),
-                            *og_num,
-                        ),
-                    ));
-                }
-                if let Some(left_path) = left_path {
-                    all_paths.push((
-                        left_path.into(),
-                        GoalPath::new_double(
-                            outer_funcs.clone(),
-                            num_funcs.clone(),
-                            op.clone(),
-                            OperationSide::Left,
-                            used.clone(),
+                            used,
                             *og_num,
                         ),
                     ));
@@ -503,6 +531,12 @@ impl GoalPaths {
 
         GoalPaths { goal, paths }
     }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
     fn get_path(&self, test: f64, used: &Used) -> Option<GoalPath> {
         if within_error(test, self.goal) {
             Some(GoalPath::new_empty())
@@ -520,9 +554,4 @@ impl GoalPaths {
             path
         }
     }
-    pub fn print_list(&self) {
-        for (n, path) in self.paths.iter() {
-            println!("{}: {}", n, path);
-        }
-    }
 }
@@ -2,8 +2,11 @@ use std::fmt::{Display, Formatter};
 
 use strum::IntoEnumIterator;
 
+use crate::finder::atom::Atom as SolvedAtom;
 use crate::finder::func::Func;
+use crate::finder::func_list::FuncList;
 use crate::finder::operation::Operation;
+use crate::finder::rules::OperationGroup;
 
 pub struct FuncAtom {
     pub atom: Atom,
@@ -18,15 +21,37 @@ impl FuncAtom {
             distribution: distribution.to_vec(),
         }
     }
-    pub fn eval_verbose(&self) {
-        self.atom.eval_verbose(&self.funcs, &self.distribution);
+    // the same expression as the other engine's atoms, with each node's
+    // functions in the order they're applied
+    pub fn to_atom(&self) -> SolvedAtom {
+        fn rec(atom: &Atom, funcs: &[Func], distribution: &[usize], i: &mut usize) -> SolvedAtom {
+            let og_i = *i;
+            if !atom.immune {
+                *i += 1;
+            }
+            let mut solved = match &atom.val {
+                AtomVal::Number(n) => SolvedAtom::new(*n),
+                AtomVal::Express { left, right, op } => {
+                    let left = rec(left, funcs, distribution, i);
+                    let right = rec(right, funcs, distribution, i);
+                    SolvedAtom::new_express(left, right, op.clone())
+                }
+            };
+            if !atom.immune {
+                let mut node_funcs = FuncList::new();
+                for func in Atom::distribute_funcs(funcs, distribution, og_i) {
+                    node_funcs.push(func.clone());
+                }
+                solved.funcs = node_funcs;
+            }
+            solved
+        }
+        rec(&self.atom, &self.funcs, &self.distribution, &mut 0)
     }
 }
 impl Display for FuncAtom {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut i = 0;
-        self.atom
-            .fmt_with_funcs(f, &self.funcs, &self.distribution, &mut i)
+        write!(f, "{}", self.to_atom())
     }
 }
 
@@ -50,7 +75,7 @@ impl Atom {
                         && left
                             .eval()
                             .and_then(|left| right.eval().map(|right| op.apply(left, right)))
-                            .map_or(false, |num| num.map_or(false, is_immune_num))
+                            .is_some_and(|num| num.is_some_and(is_immune_num))
                 }
             },
             val,
@@ -101,7 +126,7 @@ impl Atom {
         funcs: &'a [Func],
         distribution: &'a [usize],
         index: usize,
-    ) -> impl Iterator<Item = &'a Func> + DoubleEndedIterator {
+    ) -> impl DoubleEndedIterator<Item = &'a Func> {
         distribution
             .iter()
             .enumerate()
@@ -140,11 +165,11 @@ impl Atom {
         let mut power_count = 0;
         self.traverse(&mut |atom| match &atom.val {
             AtomVal::Number(..) => num_count += 1,
-            AtomVal::Express { op, .. } => match op {
-                Operation::Power => power_count += 1,
-                Operation::Root => power_count += 1,
-                _ => (),
-            },
+            AtomVal::Express { op, .. } => {
+                if is_power(op) {
+                    power_count += 1;
+                }
+            }
         });
         // extra points for all numbers used
         if num_count == 5 {
@@ -162,45 +187,6 @@ impl Atom {
         count
     }
 
-    fn fmt_with_funcs(
-        &self,
-        f: &mut Formatter<'_>,
-        funcs: &[Func],
-        distribution: &[usize],
-        i: &mut usize,
-    ) -> std::fmt::Result {
-        let mut end_str = String::new();
-        if !self.immune {
-            // write parantheses around innermost expression
-            write!(f, "(")?;
-            end_str.insert_str(0, ")");
-            for func in Atom::distribute_funcs(funcs, distribution, *i).rev() {
-                // behind functions need parantheses around them
-                if func.is_behind() {
-                    write!(f, "(")?;
-                    end_str.insert_str(0, &format!("){}", func));
-                // in front of functions don't need parantheses
-                } else {
-                    write!(f, "{}", func)?;
-                }
-            }
-
-            *i += 1;
-        }
-
-        match &self.val {
-            AtomVal::Number(n) => write!(f, "{}", n)?,
-            AtomVal::Express { left, right, op } => {
-                write!(f, "(")?;
-                left.fmt_with_funcs(f, funcs, distribution, i)?;
-                write!(f, " {} ", op)?;
-                right.fmt_with_funcs(f, funcs, distribution, i)?;
-                write!(f, ")")?;
-            }
-        };
-
-        write!(f, "{}", end_str)
-    }
     pub fn eval(&self) -> Option<f64> {
         self.eval_with_funcs(&[], &[], false)
     }
@@ -221,7 +207,7 @@ impl Atom {
         limit: bool,
     ) -> Option<f64> {
         // skip if immune, because immune nodes are not counted in the distribution
-        let og_i = i.clone();
+        let og_i = *i;
         if !self.immune {
             *i += 1;
         }
@@ -239,56 +225,9 @@ impl Atom {
         Atom::distribute_funcs(funcs, distribution, og_i)
             .fold(num, |acc, func| func.apply_if_limit(acc?, limit))
     }
-
-    pub fn eval_verbose(&self, funcs: &[Func], distribution: &[usize]) -> Option<f64> {
-        let mut i = 0;
-        self.eval_verbose_rec(funcs, distribution, &mut i)
-    }
-    fn eval_verbose_rec(
-        &self,
-        funcs: &[Func],
-        distribution: &[usize],
-        i: &mut usize,
-    ) -> Option<f64> {
-        let og_i = i.clone();
-        if !self.immune {
-            *i += 1;
-        }
-        let num = match &self.val {
-            AtomVal::Express { left, right, op } => {
-                match (
-                    left.eval_verbose_rec(funcs, distribution, i),
-                    right.eval_verbose_rec(funcs, distribution, i),
-                ) {
-                    (Some(left), Some(right)) => {
-                        let ret = op.apply(left, right);
-                        println!("{} {} {} = {}", left, op, right, ret.unwrap_or(f64::NAN));
-                        ret
-                    }
-                    _ => None,
-                }
-            }
-            AtomVal::Number(n) => Some(*n),
-        };
-        if self.immune {
-            return num;
-        }
-        Atom::distribute_funcs(funcs, distribution, og_i).fold(num, |num, func| {
-            num.and_then(|n| {
-                let out = func.apply(n);
-                println!("{}({}) = {}", func, n, out.unwrap_or(f64::NAN));
-                out
-            })
-        })
-    }
 }
-impl Display for Atom {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match &self.val {
-            AtomVal::Number(n) => write!(f, "{}", n),
-            AtomVal::Express { left, right, op } => {
-                write!(f, "({} {} {})", left, op, right)
-            }
-        }
-    }
+
+// powers and roots score a point, like in the other engine
+pub fn is_power(op: &Operation) -> bool {
+    matches!(op.group(), OperationGroup::Power | OperationGroup::Root)
 }
@@ -1,7 +1,7 @@
-use crate::finder::atom::Atom;
-use crate::finder::codon::{codons_from_atom, Codon, CodonVal};
+use super::atom::Atom;
+use super::codon::{codons_from_atom, Codon, CodonVal};
+use super::Used;
 use crate::finder::func::Func;
-use crate::finder::Used;
 use rayon::prelude::*;
 use std::collections::HashMap;
 
@@ -12,12 +12,9 @@ pub struct AtomStore {
 
 impl AtomStore {
     pub fn new(atoms: Vec<(Atom, Used)>) -> AtomStore {
-        let mut atom_map = HashMap::new();
+        let mut atom_map: HashMap<u32, Vec<(Atom, Used)>> = HashMap::new();
         for (atom, used) in atoms {
-            atom_map
-                .entry(atom.score())
-                .or_insert(Vec::new())
-                .push((atom, used));
+            atom_map.entry(atom.score()).or_default().push((atom, used));
         }
         let mut sorted_scores: Vec<u32> = atom_map.keys().copied().collect();
         sorted_scores.sort();
@@ -31,7 +28,10 @@ impl AtomStore {
         }
     }
     pub fn len(&self) -> usize {
-        self.atom_groups.iter().map(|(_, v)| v.len()).sum()
+        self.atom_groups.values().map(AtomGroup::len).sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
     pub fn iter(&self) -> impl Iterator<Item = (&u32, &AtomGroup)> {
         // release from smallest base_score to largest
@@ -63,9 +63,12 @@ impl AtomGroup {
             codons,
         }
     }
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.atoms.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.atoms.is_empty()
+    }
     pub fn par_iter(&self) -> impl ParallelIterator<Item = (&(Atom, Used), &(usize, usize))> {
         self.atoms.par_iter().zip(self.codon_info.par_iter())
     }
@@ -73,7 +76,6 @@ impl AtomGroup {
     pub fn iter(&self) -> impl Iterator<Item = (&(Atom, Used), &(usize, usize))> {
         self.atoms.iter().zip(self.codon_info.iter())
     }
-    #[allow(invalid_value)]
     pub fn eval_with_funcs(
         &self,
         codon_index: usize,
@@ -82,11 +84,8 @@ impl AtomGroup {
         distribution: &[usize],
         limit: bool,
     ) -> Option<f64> {
-        let mut calc_box: [f64; 5];
-        // leave calc_box uninitialized
-        // this is safe because we will always write to it before reading from it
-        // the compiler can't know that we will set the values before reading them
-        unsafe { calc_box = std::mem::MaybeUninit::uninit().assume_init() };
+        // every box is written before it's read
+        let mut calc_box = [0.; 5];
 
         for codon in self.codons[codon_index..codon_index + codon_count].iter() {
             let Codon {
@@ -106,9 +105,8 @@ impl AtomGroup {
                 }
                 CodonVal::Number { num } => (*num, *calc_box_save, *func_index),
             };
-            let distributed = Atom::distribute_funcs(funcs, distribution, func_index);
-            let num = distributed.fold(Some(num), |acc, func| func.apply_if_limit(acc?, limit));
-            calc_box[calc_box_save] = num?;
+            calc_box[calc_box_save] = Atom::distribute_funcs(funcs, distribution, func_index)
+                .try_fold(num, |acc, func| func.apply_if_limit(acc, limit))?;
         }
         Some(calc_box[0])
     }
@@ -1,4 +1,4 @@
-use crate::finder::atom::{Atom, AtomVal};
+use super::atom::{is_power, Atom, AtomVal};
 use crate::finder::operation::Operation;
 use std::cmp::max;
 
@@ -47,14 +47,14 @@ pub fn codons_from_atom(atom: &Atom) -> Vec<Codon> {
         calc_box_index: &mut usize,
         i: &mut usize,
     ) -> usize {
-        let func_index = i.clone();
+        let func_index = *i;
         if !atom.immune {
             *i += 1;
         }
         match &atom.val {
             AtomVal::Express { left, right, op } => {
-                let left_id = rec(&*left, codons, calc_box_index, i);
-                let right_id = rec(&*right, codons, calc_box_index, i);
+                let left_id = rec(left, codons, calc_box_index, i);
+                let right_id = rec(right, codons, calc_box_index, i);
                 codons.push(DependCodon {
                     val: DependCodonVal::Express {
                         left_id,
@@ -80,7 +80,7 @@ pub fn codons_from_atom(atom: &Atom) -> Vec<Codon> {
         codons.len() - 1
     }
 
-    fn index_from_id(depend_codons: &Vec<DependCodon>, id: usize) -> usize {
+    fn index_from_id(depend_codons: &[DependCodon], id: usize) -> usize {
         depend_codons
             .iter()
             .enumerate()
@@ -121,15 +121,9 @@ pub fn codons_from_atom(atom: &Atom) -> Vec<Codon> {
     fn reorder_depend_codons(depend_codons: &mut Vec<DependCodon>) {
         let priority_codon_ids: Vec<usize> = depend_codons
             .iter()
-            .filter(|codon| {
-                matches!(
-                    codon.val,
-                    DependCodonVal::Express {
-                        op: Operation::Power | Operation::Root,
-                        ..
-                    }
-                )
-            })
+            .filter(
+                |codon| matches!(&codon.val, DependCodonVal::Express { op, .. } if is_power(op)),
+            )
             .map(|codon| codon.id)
             // loop thru depend_codons backwards so that atoms farther down the tree
             // are moved to the front of the list last, giving them priority
@@ -1,8 +1,10 @@
+use std::cell::Cell;
+
 const ROUNDING_ERROR: f64 = 0.0000000001;
-const MAX_NUM_SIZE: f64 = 1e15;
+pub const MAX_NUM_SIZE: f64 = 1e15;
 
-const POWER_DELTA: f64 = 0.00001;
-const DIVIDE_DELTA: f64 = 0.00001;
+pub const POWER_DELTA: f64 = 0.00001;
+pub const DIVIDE_DELTA: f64 = 0.00001;
 
 // based off https://dateo-math-game.com/inputLogic.js
 fn within_rounding_error(x: f64, y: f64, delta: f64) -> bool {
@@ -52,7 +54,7 @@ fn general_power(left: f64, right: f64) -> Option<f64> {
 }
 pub fn power(left: f64, right: f64) -> Option<f64> {
     if left >= 0. {
-        return general_power(left, right);
+        general_power(left, right)
     } else {
         None
     }
@@ -60,7 +62,7 @@ pub fn power(left: f64, right: f64) -> Option<f64> {
 // this function is only used for the reverse operation
 pub fn power_neg(left: f64, right: f64) -> Option<f64> {
     if left < 0. {
-        return general_power(left, right);
+        general_power(left, right)
     } else {
         None
     }
@@ -78,7 +80,7 @@ pub fn root(left: f64, right: f64) -> Option<f64> {
 }
 
 pub fn square_root(num: f64) -> Option<f64> {
-    Some(power(num, 0.5)?)
+    power(num, 0.5)
 }
 
 pub fn summation(num: f64) -> Option<f64> {
@@ -123,7 +125,7 @@ pub fn square_root_rev(num: f64) -> Option<f64> {
     if num < 0. {
         return None;
     }
-    Some(power(num, 2.)?)
+    power(num, 2.)
 }
 
 pub fn summation_rev(num: f64) -> Option<f64> {
@@ -224,6 +226,19 @@ pub fn within_error(test: f64, goal: f64) -> bool {
     (test - goal).abs() < ROUNDING_ERROR
 }
 
+thread_local! {
+    static LIMIT_HIT: Cell<bool> = const { Cell::new(false) };
+}
+
 pub fn within_limit(num: &f64) -> bool {
-    num.abs() < MAX_NUM_SIZE
+    let within = num.abs() < MAX_NUM_SIZE;
+    if !within {
+        LIMIT_HIT.with(|hit| hit.set(true));
+    }
+    within
+}
+
+// whether within_limit rejected a number since the last call
+pub fn take_limit_hit() -> bool {
+    LIMIT_HIT.with(|hit| hit.replace(false))
 }
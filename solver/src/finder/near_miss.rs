@@ -0,0 +1,126 @@
+use super::atom::Atom;
+use super::bounds::BoundSet;
+use super::joiner::{get_joiners, Memo};
+use super::math::within_error;
+use super::score::Score;
+use crate::error::SolveError;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone)]
+pub struct Miss {
+    pub value: f64,
+    pub score: Score,
+    pub atom: Atom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MissCause {
+    // these limits cut the search off, so searching past them could still reach the goal
+    Bounds(BoundSet),
+    // no limit was hit, so searching deeper won't help
+    Shapes,
+}
+
+#[derive(Debug, Clone)]
+pub struct NearMiss {
+    pub below: Option<Miss>,
+    pub above: Option<Miss>,
+    pub cause: MissCause,
+}
+
+// the up side of every joiner doesn't depend on the goal, so the values the
+// numbers reach are already in the memo after the main search, and the closest
+// ones are read from there without searching again
+pub fn find_near_miss(
+    nums: &[(u8, f64)],
+    goal: f64,
+    bounds_hit: BoundSet,
+    memo: &Memo,
+) -> Result<NearMiss, SolveError> {
+    let mut below: Option<Miss> = None;
+    let mut above: Option<Miss> = None;
+    let mut seen = HashSet::new();
+    for num_count in (1..=nums.len()).rev() {
+        for mut joiner in get_joiners(num_count)? {
+            joiner.visit_up_vals(nums, memo, &mut seen, |val, build_atom| {
+                if within_error(val.num, goal) {
+                    return;
+                }
+                let closest = if val.num < goal {
+                    &mut below
+                } else {
+                    &mut above
+                };
+                let score = val.score.resolve();
+                if closest
+                    .as_ref()
+                    .is_some_and(|miss| !is_closer(val.num, score, miss, goal))
+                {
+                    return;
+                }
+                let atom = build_atom();
+                if atom.test(val.num) {
+                    *closest = Some(Miss {
+                        value: val.num,
+                        score,
+                        atom,
+                    });
+                }
+            });
+        }
+    }
+    Ok(NearMiss {
+        below,
+        above,
+        cause: if bounds_hit.is_empty() {
+            MissCause::Shapes
+        } else {
+            MissCause::Bounds(bounds_hit)
+        },
+    })
+}
+
+// ties go to the higher score
+fn is_closer(value: f64, score: Score, miss: &Miss, goal: f64) -> bool {
+    let (distance, miss_distance) = ((value - goal).abs(), (miss.value - goal).abs());
+    if within_error(distance, miss_distance) {
+        score.score() > miss.score.score()
+    } else {
+        distance < miss_distance
+    }
+}
+
+impl Display for Miss {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} with score {}: {}", self.value, self.score, self.atom)
+    }
+}
+
+impl Display for NearMiss {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.cause {
+            MissCause::Bounds(bounds_hit) => writeln!(
+                f,
+                "no solution found, a search past these limits might still find one: {}",
+                bounds_hit
+                    .iter()
+                    .map(|bound| bound.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
+            MissCause::Shapes => writeln!(
+                f,
+                "no solution found, every tree shape was searched without hitting a limit"
+            )?,
+        }
+        match &self.below {
+            Some(miss) => writeln!(f, "closest below: {}", miss)?,
+            None => writeln!(f, "nothing reachable below")?,
+        }
+        match &self.above {
+            Some(miss) => write!(f, "closest above: {}", miss),
+            None => write!(f, "nothing reachable above"),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::finder::math::*;
+use crate::finder::rules::{OperationGroup, Rules};
 
 use std::fmt::{Display, Formatter};
 use strum::IntoEnumIterator;
@@ -25,15 +26,18 @@ impl Operation {
         num: f64,
         other_num: f64,
         rev: bool,
+        rules: Rules,
     ) -> impl Iterator<Item = (Operation, f64)> {
-        Operation::iter().flat_map(move |op| {
-            if rev {
-                op.apply_rev(num, other_num)
-            } else {
-                op.apply(num, other_num)
-            }
-            .map(|num| (op.clone(), num))
-        })
+        Operation::iter()
+            .filter(move |op| rules.allows(op))
+            .flat_map(move |op| {
+                if rev {
+                    op.apply_rev(num, other_num)
+                } else {
+                    op.apply(num, other_num)
+                }
+                .map(|num| (op.clone(), num))
+            })
     }
 
     pub fn apply_no_limit(&self, left: f64, right: f64) -> Option<f64> {
@@ -76,8 +80,8 @@ impl Operation {
             Operation::PowerSwitch => power_rev_right(num, res),
             Operation::PowerNeg => power_neg_rev_left(num, res),
             Operation::PowerNegSwitch => power_neg_rev_right(num, res),
-            Operation::Root => root_rev_left(num, res),
-            Operation::RootSwitch => root_rev_right(num, res),
+            Operation::Root => root_rev_right(num, res),
+            Operation::RootSwitch => root_rev_left(num, res),
         }
         .filter(|res| !res.is_nan())
         .filter(within_limit)
@@ -89,16 +93,31 @@ impl Operation {
     //         _ => false,
     //     }
     // }
-    pub fn is_switched(&self) -> bool {
+    pub fn group(&self) -> OperationGroup {
         match self {
-            Operation::SubtractSwitch
-            | Operation::DivideSwitch
+            Operation::Add | Operation::Subtract | Operation::SubtractSwitch => {
+                OperationGroup::Addition
+            }
+            Operation::Multiply | Operation::Divide | Operation::DivideSwitch => {
+                OperationGroup::Multiplication
+            }
+            Operation::Power
             | Operation::PowerSwitch
-            | Operation::RootSwitch
-            | Operation::PowerNegSwitch => true,
-            _ => false,
+            | Operation::PowerNeg
+            | Operation::PowerNegSwitch => OperationGroup::Power,
+            Operation::Root | Operation::RootSwitch => OperationGroup::Root,
         }
     }
+    pub fn is_switched(&self) -> bool {
+        matches!(
+            self,
+            Operation::SubtractSwitch
+                | Operation::DivideSwitch
+                | Operation::PowerSwitch
+                | Operation::RootSwitch
+                | Operation::PowerNegSwitch
+        )
+    }
 }
 impl Display for Operation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
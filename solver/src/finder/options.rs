@@ -0,0 +1,148 @@
+use super::func_list;
+use super::rules::Rules;
+use super::stop::Stop;
+use super::strategy::StrategyKind;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+// a FuncList only fits 32 functions, so this leaves room for a few others
+pub const MAX_SQUARE_ROOTS: usize = 30;
+
+// a value gets up to one more function than its depth, and when the two sides
+// meet both values' functions are scored from one FuncList, so each side only
+// gets half of it
+pub const MAX_DEPTH: usize = func_list::CAPACITY / 2 - 1;
+
+// how many functions can be chained on each side of a joiner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Depth {
+    pub num: usize,
+    pub goal: usize,
+}
+
+impl Depth {
+    pub fn both(depth: usize) -> Self {
+        Self {
+            num: depth,
+            goal: depth,
+        }
+    }
+    pub fn for_side(&self, is_goal: bool) -> usize {
+        if is_goal {
+            self.goal
+        } else {
+            self.num
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    Quick,
+    Normal,
+    Exhaustive,
+}
+
+// which search solve runs
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumIter,
+    EnumString,
+    Display,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Engine {
+    // joiners meeting in the middle, and the strategies
+    #[default]
+    Joiner,
+    // the engine from before the joiners, see finder::legacy
+    Legacy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub depth: Depth,
+    pub squares_depth: usize,
+    // most square roots the squares trick will stack
+    pub max_square_roots: usize,
+}
+
+impl SearchOptions {
+    pub fn quick() -> Self {
+        Self {
+            depth: Depth::both(2),
+            squares_depth: 2,
+            max_square_roots: 10,
+        }
+    }
+    pub fn normal() -> Self {
+        Self {
+            depth: Depth::both(5),
+            squares_depth: 4,
+            max_square_roots: MAX_SQUARE_ROOTS,
+        }
+    }
+    pub fn exhaustive() -> Self {
+        Self {
+            depth: Depth::both(7),
+            squares_depth: 5,
+            max_square_roots: MAX_SQUARE_ROOTS,
+        }
+    }
+    pub fn from_preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Quick => Self::quick(),
+            Preset::Normal => Self::normal(),
+            Preset::Exhaustive => Self::exhaustive(),
+        }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self::normal()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveOptions {
+    pub rules: Rules,
+    pub search: SearchOptions,
+    // run in order before the general search
+    pub strategies: Vec<StrategyKind>,
+    // search low depths first so a solution comes out quickly, then refine it
+    pub anytime: bool,
+    // ends the search early, with what was found so far
+    pub stop: Stop,
+    pub engine: Engine,
+    // time the phases for the stats. off unless asked for, since the clock
+    // panics on wasm32-unknown-unknown
+    pub timed: bool,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            rules: Rules::default(),
+            search: SearchOptions::default(),
+            strategies: StrategyKind::iter().collect(),
+            anytime: false,
+            stop: Stop::new(),
+            engine: Engine::default(),
+            timed: false,
+        }
+    }
+}
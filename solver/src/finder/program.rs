@@ -0,0 +1,179 @@
+use super::atom::{Atom, Val};
+use super::func_list::FuncList;
+use super::math::within_error;
+use super::operation::Operation;
+use itertools::Itertools;
+
+// an atom compiled into a flat list of steps over registers. compiling costs
+// more than it saves when an atom is only checked once (see the atom_*_test
+// benches), so Atom::test walks the tree and this is kept to check it against.
+// powers and roots, with what they depend on, run first, since they're the most
+// likely to fail and a failure ends the run
+#[derive(Debug, Clone)]
+pub struct Program {
+    steps: Vec<Step>,
+    // one for each number, an operation keeps its result in its left side's
+    registers: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Instr {
+    Load(f64),
+    Apply {
+        left: usize,
+        right: usize,
+        op: Operation,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    instr: Instr,
+    // applied to the result before it's stored
+    funcs: FuncList,
+    register: usize,
+}
+
+impl Program {
+    pub fn from_atom(atom: &Atom) -> Self {
+        // the atom's nodes in post order, children before their parent
+        struct Node {
+            instr: Instr,
+            funcs: FuncList,
+            register: usize,
+            // the nodes of the operands, for operations
+            children: Option<(usize, usize)>,
+        }
+        fn flatten(atom: &Atom, nodes: &mut Vec<Node>, registers: &mut usize) -> usize {
+            let node = match &atom.val {
+                Val::Num(n) => {
+                    *registers += 1;
+                    Node {
+                        instr: Instr::Load(*n),
+                        funcs: atom.funcs,
+                        register: *registers - 1,
+                        children: None,
+                    }
+                }
+                Val::Express { left, right, op } => {
+                    let left = flatten(left, nodes, registers);
+                    let right = flatten(right, nodes, registers);
+                    Node {
+                        instr: Instr::Apply {
+                            left: nodes[left].register,
+                            right: nodes[right].register,
+                            op: op.clone(),
+                        },
+                        funcs: atom.funcs,
+                        register: nodes[left].register,
+                        children: Some((left, right)),
+                    }
+                }
+                Val::Hole => panic!("compile with hole"),
+            };
+            nodes.push(node);
+            nodes.len() - 1
+        }
+        fn emit(id: usize, nodes: &[Node], emitted: &mut [bool], order: &mut Vec<usize>) {
+            if emitted[id] {
+                return;
+            }
+            if let Some((left, right)) = nodes[id].children {
+                emit(left, nodes, emitted, order);
+                emit(right, nodes, emitted, order);
+            }
+            emitted[id] = true;
+            order.push(id);
+        }
+
+        let mut nodes = Vec::new();
+        let mut registers = 0;
+        flatten(atom, &mut nodes, &mut registers);
+
+        // inner powers come before outer ones in post order, so they go first
+        let mut emitted = vec![false; nodes.len()];
+        let mut order = Vec::with_capacity(nodes.len());
+        let failing_first = nodes.iter().positions(|node| {
+            matches!(
+                node.instr,
+                Instr::Apply {
+                    op: Operation::Power | Operation::Root,
+                    ..
+                }
+            )
+        });
+        for id in failing_first.chain(0..nodes.len()) {
+            emit(id, &nodes, &mut emitted, &mut order);
+        }
+        let steps = order
+            .into_iter()
+            .map(|id| Step {
+                instr: nodes[id].instr.clone(),
+                funcs: nodes[id].funcs,
+                register: nodes[id].register,
+            })
+            .collect();
+        Self { steps, registers }
+    }
+    // the same as evaluating the atom, None if any step fails
+    pub fn eval(&self, limit: bool) -> Option<f64> {
+        let mut registers = vec![0.; self.registers];
+        for step in &self.steps {
+            let num = match &step.instr {
+                Instr::Load(n) => *n,
+                Instr::Apply { left, right, op } => {
+                    op.apply_if_limit(registers[*left], registers[*right], limit)?
+                }
+            };
+            registers[step.register] = step
+                .funcs
+                .iter()
+                .try_fold(num, |num, func| func.apply_if_limit(num, limit))?;
+        }
+        // the root's register is its leftmost number's, the first one
+        Some(registers[0])
+    }
+    // false if the goal can still be hit with some of the functions taken out
+    pub fn all_funcs_necessary(&self, goal: f64) -> bool {
+        let mut registers = vec![Vec::new(); self.registers];
+        for step in &self.steps {
+            let nums = match &step.instr {
+                Instr::Load(n) => vec![*n],
+                Instr::Apply { left, right, op } => registers[*left]
+                    .iter()
+                    .cartesian_product(&registers[*right])
+                    .filter_map(|(l, r)| op.apply_no_limit(*l, *r))
+                    .collect(),
+            };
+            registers[step.register] = with_removed_funcs(nums, step.funcs);
+        }
+        // the last value keeps every function, it's the atom itself
+        registers[0]
+            .iter()
+            .rev()
+            .skip(1)
+            .all(|num| !within_error(*num, goal))
+    }
+}
+
+// every value with runs of the same function cut short, ending with all of
+// them applied to the last num
+fn with_removed_funcs(nums: Vec<f64>, funcs: FuncList) -> Vec<f64> {
+    if funcs.is_empty() {
+        return nums;
+    }
+    funcs
+        .iter()
+        .group_by(|func| func.clone())
+        .into_iter()
+        .map(|(func, group)| (0..=group.count()).map(move |i| (func.clone(), i)))
+        .multi_cartesian_product()
+        .flat_map(|product| {
+            nums.iter().filter_map(move |num| {
+                product.iter().try_fold(*num, |num, (func, func_repeat)| {
+                    (0..*func_repeat).try_fold(num, |num, _| func.apply_no_limit(num))
+                })
+            })
+        })
+        .collect()
+}
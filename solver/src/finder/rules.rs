@@ -0,0 +1,137 @@
+use super::atom::{Atom, Val};
+use super::func::Func;
+use super::operation::Operation;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+// groups of operations that are turned on and off together,
+// e.g. subtraction is only allowed if addition is
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum OperationGroup {
+    Addition,
+    Multiplication,
+    Power,
+    Root,
+}
+
+impl OperationGroup {
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum RulePreset {
+    // every operation the game allows
+    Standard,
+    // only + - * /
+    Basic,
+}
+
+// how a family of squares solutions that only stopped at the square root cap is ranked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display, Default)]
+#[strum(serialize_all = "kebab-case")]
+pub enum FamilyRanking {
+    // by the capped instance that was actually built
+    #[default]
+    Found,
+    // by the largest instance that could be built and tested
+    Largest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    // bit set of enabled operation groups
+    operation_groups: u8,
+    // bit set of disabled functions
+    disabled_funcs: u8,
+    pub family_ranking: FamilyRanking,
+}
+
+impl Rules {
+    pub fn standard() -> Self {
+        Self::from_groups(OperationGroup::iter())
+    }
+    pub fn basic() -> Self {
+        Self::from_groups([OperationGroup::Addition, OperationGroup::Multiplication])
+    }
+    pub fn from_preset(preset: RulePreset) -> Self {
+        match preset {
+            RulePreset::Standard => Self::standard(),
+            RulePreset::Basic => Self::basic(),
+        }
+    }
+    pub fn from_groups(groups: impl IntoIterator<Item = OperationGroup>) -> Self {
+        let mut rules = Self {
+            operation_groups: 0,
+            disabled_funcs: 0,
+            family_ranking: FamilyRanking::default(),
+        };
+        for group in groups {
+            rules = rules.enable(group);
+        }
+        rules
+    }
+    pub fn enable(mut self, group: OperationGroup) -> Self {
+        self.operation_groups |= group.bit();
+        self
+    }
+    pub fn disable(mut self, group: OperationGroup) -> Self {
+        self.operation_groups &= !group.bit();
+        self
+    }
+    pub fn disable_func(mut self, func: Func) -> Self {
+        self.disabled_funcs |= func_bit(&func);
+        self
+    }
+    pub fn with_family_ranking(mut self, family_ranking: FamilyRanking) -> Self {
+        self.family_ranking = family_ranking;
+        self
+    }
+    pub fn allows_group(&self, group: OperationGroup) -> bool {
+        self.operation_groups & group.bit() != 0
+    }
+    pub fn allows(&self, op: &Operation) -> bool {
+        self.allows_group(op.group())
+    }
+    pub fn allows_func(&self, func: &Func) -> bool {
+        self.disabled_funcs & func_bit(func) == 0
+    }
+    pub fn disabled_funcs(&self) -> impl Iterator<Item = Func> + '_ {
+        Func::iter().filter(|func| !self.allows_func(func))
+    }
+    pub fn groups(&self) -> impl Iterator<Item = OperationGroup> + '_ {
+        OperationGroup::iter().filter(|group| self.allows_group(*group))
+    }
+    // checks every operation and function in the atom, used to reject user answers
+    pub fn allows_atom(&self, atom: &Atom) -> bool {
+        if !atom.funcs.iter().all(|func| self.allows_func(&func)) {
+            return false;
+        }
+        match &atom.val {
+            Val::Num(_) | Val::Hole => true,
+            Val::Express { left, right, op } => {
+                self.allows(op) && self.allows_atom(left) && self.allows_atom(right)
+            }
+        }
+    }
+}
+
+fn func_bit(func: &Func) -> u8 {
+    1 << (func.clone() as u8)
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
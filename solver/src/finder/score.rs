@@ -39,30 +39,11 @@ impl Score {
         }
     }
     pub fn resolve(mut self) -> Self {
-        const MAX_CONSECUTIVE_SQAURE_ROOT: u8 = 4;
         // self.funcs += self.pending_funcs.square_root_funcs;
         // // .min(MAX_SQAURE_ROOT_FUNCS);
         // self.funcs += self.pending_funcs.factorial_funcs;
         // self.funcs += self.pending_funcs.summation_funcs;
-        let mut current_consecutive_square_root = 0;
-        for func in self.pending_funcs.func_list.iter() {
-            match func {
-                Func::SquareRoot => {
-                    current_consecutive_square_root += 1;
-                    if current_consecutive_square_root <= MAX_CONSECUTIVE_SQAURE_ROOT {
-                        self.funcs += 1;
-                    }
-                }
-                Func::Factorial => {
-                    current_consecutive_square_root = 0;
-                    self.funcs += 1;
-                }
-                Func::Summation => {
-                    current_consecutive_square_root = 0;
-                    self.funcs += 1;
-                }
-            }
-        }
+        self.funcs += funcs_score(self.pending_funcs.func_list.iter());
 
         self.pending_funcs = PendingFuncScore {
             func_list: FuncList::new(),
@@ -70,7 +51,7 @@ impl Score {
         self
     }
     pub fn score(&self) -> u8 {
-        if self.pending_funcs.func_list.len() > 0 {
+        if !self.pending_funcs.func_list.is_empty() {
             panic!("cannot score unresolved Score");
         }
         let base_score = self.nums + self.funcs + self.power_ops + self.root_ops;
@@ -177,3 +158,29 @@ impl Add for Score {
         }
     }
 }
+
+// points for a chain of functions on one atom
+pub fn funcs_score(funcs: impl IntoIterator<Item = Func>) -> u8 {
+    const MAX_CONSECUTIVE_SQAURE_ROOT: u8 = 4;
+    let mut score = 0;
+    let mut current_consecutive_square_root = 0;
+    for func in funcs {
+        match func {
+            Func::SquareRoot => {
+                current_consecutive_square_root += 1;
+                if current_consecutive_square_root <= MAX_CONSECUTIVE_SQAURE_ROOT {
+                    score += 1;
+                }
+            }
+            Func::Factorial => {
+                current_consecutive_square_root = 0;
+                score += 1;
+            }
+            Func::Summation => {
+                current_consecutive_square_root = 0;
+                score += 1;
+            }
+        }
+    }
+    score
+}
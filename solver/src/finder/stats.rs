@@ -0,0 +1,192 @@
+use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+// where a solve spent its time and how much work each part did, to see what's
+// worth speeding up
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveStats {
+    // each phase, like "squares" or "other", in the order they ran
+    pub phases: Vec<(String, Duration)>,
+    // the general search's time on each count of numbers, most numbers first
+    pub num_counts: Vec<(usize, Duration)>,
+    // joiners and permutations of the numbers gone through, strategies included
+    pub joiners: u64,
+    pub permutations: u64,
+    // memo lookups while filling the trees
+    pub memo_hits: u64,
+    pub memo_misses: u64,
+    // vals made for the keys that missed
+    pub vals: u64,
+    // the keys that made the most vals, most first
+    pub top_keys: Vec<(String, u64)>,
+    // pairs of up and down vals that met at the same value
+    pub intersections: u64,
+    // atoms built from a pair or a strategy and checked with Atom::test. the
+    // squares trick builds its atoms without intersections
+    pub tested: u64,
+    pub accepted: u64,
+    // the most keys a memo had
+    pub peak_memo: usize,
+}
+
+// how many of the keys with the most vals are kept
+const TOP_KEYS: usize = 5;
+
+impl SolveStats {
+    // counts the vals a missed key made
+    pub fn add_key_vals(&mut self, key: &str, vals: u64) {
+        self.vals += vals;
+        if self.top_keys.len() == TOP_KEYS
+            && self
+                .top_keys
+                .last()
+                .is_some_and(|(_, least)| *least >= vals)
+        {
+            return;
+        }
+        let at = self.top_keys.partition_point(|(_, more)| *more >= vals);
+        self.top_keys.insert(at, (key.to_string(), vals));
+        self.top_keys.truncate(TOP_KEYS);
+    }
+    // None if nothing was looked up
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.memo_hits + self.memo_misses;
+        (lookups > 0).then(|| self.memo_hits as f64 / lookups as f64)
+    }
+    // None if every lookup hit
+    pub fn vals_per_key(&self) -> Option<f64> {
+        (self.memo_misses > 0).then(|| self.vals as f64 / self.memo_misses as f64)
+    }
+    pub fn to_json(&self) -> Value {
+        json!({
+            "phases": self.phases.iter().map(|(phase, time)| {
+                json!({ "phase": phase, "seconds": time.as_secs_f64() })
+            }).collect::<Vec<_>>(),
+            "num_counts": self.num_counts.iter().map(|(nums, time)| {
+                json!({ "nums": nums, "seconds": time.as_secs_f64() })
+            }).collect::<Vec<_>>(),
+            "joiners": self.joiners,
+            "permutations": self.permutations,
+            "memo_hits": self.memo_hits,
+            "memo_misses": self.memo_misses,
+            "hit_rate": self.hit_rate(),
+            "vals": self.vals,
+            "vals_per_key": self.vals_per_key(),
+            "top_keys": self.top_keys.iter().map(|(key, vals)| {
+                json!({ "key": key, "vals": vals })
+            }).collect::<Vec<_>>(),
+            "intersections": self.intersections,
+            "tested": self.tested,
+            "accepted": self.accepted,
+            "peak_memo": self.peak_memo,
+        })
+    }
+}
+
+impl Display for SolveStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "stats:")?;
+        for (phase, time) in &self.phases {
+            write!(f, "\n  {}: {:.2?}", phase, time)?;
+        }
+        for (nums, time) in &self.num_counts {
+            let numbers = if *nums == 1 { "number" } else { "numbers" };
+            write!(f, "\n  other with {} {}: {:.2?}", nums, numbers, time)?;
+        }
+        write!(
+            f,
+            "\n  joiners: {}, permutations: {}",
+            self.joiners, self.permutations
+        )?;
+        write!(
+            f,
+            "\n  memo: {} hits, {} misses",
+            self.memo_hits, self.memo_misses
+        )?;
+        if let Some(hit_rate) = self.hit_rate() {
+            write!(f, " ({:.1}% hits)", hit_rate * 100.)?;
+        }
+        if let Some(vals_per_key) = self.vals_per_key() {
+            write!(f, ", {:.1} vals per key", vals_per_key)?;
+        }
+        write!(f, ", at most {} keys", self.peak_memo)?;
+        for (key, vals) in &self.top_keys {
+            write!(f, "\n    {} vals: {}", vals, key)?;
+        }
+        write!(
+            f,
+            "\n  intersections: {}, tested: {}, accepted: {}",
+            self.intersections, self.tested, self.accepted
+        )
+    }
+}
+
+thread_local! {
+    // the stats of the solve running on this thread, so the joiners and trees
+    // can count without every signature taking them
+    static RECORDING: RefCell<SolveStats> = RefCell::default();
+    // whether the solve running on this thread reads the clock
+    static TIMED: Cell<bool> = const { Cell::new(false) };
+}
+
+// clears what an earlier solve recorded
+pub fn start(timed: bool) {
+    RECORDING.take();
+    TIMED.set(timed);
+}
+
+pub fn record(f: impl FnOnce(&mut SolveStats)) {
+    RECORDING.with_borrow_mut(f)
+}
+
+// what was recorded since the last call
+pub fn take() -> SolveStats {
+    RECORDING.take()
+}
+
+enum Timed {
+    Phase(String),
+    NumCount(usize),
+}
+
+// adds the time until it's dropped to the stats, if the solve is timed
+pub struct Timing {
+    timed: Timed,
+    started: Option<Instant>,
+}
+
+fn time(timed: Timed) -> Timing {
+    Timing {
+        timed,
+        started: TIMED.get().then(Instant::now),
+    }
+}
+
+pub fn time_phase(phase: impl Into<String>) -> Timing {
+    time(Timed::Phase(phase.into()))
+}
+
+// the anytime levels each search every count, so the times add up
+pub fn time_num_count(num_count: usize) -> Timing {
+    time(Timed::NumCount(num_count))
+}
+
+impl Drop for Timing {
+    fn drop(&mut self) {
+        let Some(started) = self.started else {
+            return;
+        };
+        let time = started.elapsed();
+        record(|stats| match &mut self.timed {
+            Timed::Phase(phase) => stats.phases.push((std::mem::take(phase), time)),
+            Timed::NumCount(num_count) => {
+                match stats.num_counts.iter_mut().find(|(n, _)| n == num_count) {
+                    Some((_, total)) => *total += time,
+                    None => stats.num_counts.push((*num_count, time)),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// lets another thread, or a deadline, cut a search short. the search checks it
+// between permutations of each joiner, so it stops soon after, with the best
+// solution found so far. clones share the same flag
+#[derive(Clone, Default)]
+pub struct Stop {
+    flag: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl Stop {
+    // never stops unless stop is called
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            flag: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+    pub fn stop(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+    pub fn is_stopped(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+impl Debug for Stop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stop")
+            .field("stopped", &self.is_stopped())
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+// two stops are the same if they share a flag
+impl PartialEq for Stop {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.flag, &other.flag) && self.deadline == other.deadline
+    }
+}
+
+impl Eq for Stop {}
+
+thread_local! {
+    // the stop of the search running on this thread, so joiners deep inside
+    // strategies can check it without every signature taking it
+    static WATCHED: RefCell<Option<Stop>> = const { RefCell::new(None) };
+}
+
+// joiners on this thread check the stop until the guard is dropped
+pub fn watch(stop: &Stop) -> Watching {
+    let previous = WATCHED.with(|watched| watched.replace(Some(stop.clone())));
+    Watching { previous }
+}
+
+pub fn watched_is_stopped() -> bool {
+    WATCHED.with(|watched| watched.borrow().as_ref().is_some_and(Stop::is_stopped))
+}
+
+pub struct Watching {
+    previous: Option<Stop>,
+}
+
+impl Drop for Watching {
+    fn drop(&mut self) {
+        WATCHED.with(|watched| *watched.borrow_mut() = self.previous.take());
+    }
+}
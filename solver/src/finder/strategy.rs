@@ -0,0 +1,77 @@
+pub mod squares;
+
+use super::atom::Atom;
+use super::bounds::BoundSet;
+use super::family::Family;
+use super::joiner::Memo;
+use super::options::SolveOptions;
+use super::rules::Rules;
+use super::score::Score;
+use crate::error::SolveError;
+use squares::Squares;
+use std::fmt::{Display, Formatter};
+use strum_macros::{Display, EnumIter, EnumString};
+
+// patterns that inflate the score in ways the general search can't reach
+// within its depth, run before the general search so it has a score to beat
+pub trait Strategy {
+    fn kind(&self) -> StrategyKind;
+    // whether the pattern can be built at all under the rules
+    fn is_allowed(&self, rules: &Rules) -> bool;
+    // calls back with every solution that beats best_score, raising it each time
+    fn solve(
+        &self,
+        nums: &[(u8, f64)],
+        goal: f64,
+        options: &SolveOptions,
+        memo: &mut Memo,
+        callback: &dyn Fn(Score, &Atom),
+        best_score: &mut u8,
+    ) -> Result<StrategyResult, SolveError>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StrategyResult {
+    // the bounds that cut off part of the search
+    pub bounds_hit: BoundSet,
+    // solutions that were only stopped by a cap
+    pub families: Vec<Family>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum StrategyKind {
+    // raise to a power of 2 and take square roots to undo it
+    Squares,
+}
+
+impl StrategyKind {
+    pub fn build(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Squares => Box::new(Squares),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyReport {
+    pub kind: StrategyKind,
+    // best score found by this strategy, 0 if it didn't beat the earlier ones
+    pub best_score: u8,
+    pub bounds_hit: BoundSet,
+    pub families: Vec<Family>,
+}
+
+impl Display for StrategyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.best_score == 0 {
+            write!(f, "{}: nothing better found", self.kind)?;
+        } else {
+            write!(f, "{}: best score {}", self.kind, self.best_score)?;
+        }
+        if !self.families.is_empty() {
+            write!(f, ", {} squares families", self.families.len())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use super::{Strategy, StrategyKind, StrategyResult};
+use crate::error::SolveError;
+use crate::finder::atom::Atom;
+use crate::finder::bounds::{Bound, BoundSet};
+use crate::finder::family::Family;
+use crate::finder::func::Func;
+use crate::finder::func_list;
+use crate::finder::joiner::{get_joiners, AtomFilter, Memo};
+use crate::finder::operation::Operation;
+use crate::finder::options::{Depth, SolveOptions};
+use crate::finder::rules::Rules;
+use crate::finder::score::Score;
+use crate::finder::stats;
+use itertools::Itertools;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+// how many power of 2 expressions to try for each square
+const SQUARES_TAKE: usize = 3;
+
+// takes the square root of (x ^ 2^n) n times to get x back,
+// scoring a function point for each square root
+pub struct Squares;
+
+impl Strategy for Squares {
+    fn kind(&self) -> StrategyKind {
+        StrategyKind::Squares
+    }
+    fn is_allowed(&self, rules: &Rules) -> bool {
+        rules.allows(&Operation::Power) && rules.allows_func(&Func::SquareRoot)
+    }
+    fn solve(
+        &self,
+        nums: &[(u8, f64)],
+        goal: f64,
+        options: &SolveOptions,
+        memo: &mut Memo,
+        callback: &dyn Fn(Score, &Atom),
+        best_score: &mut u8,
+    ) -> Result<StrategyResult, SolveError> {
+        // only some expressions of the squares form are tried, every time
+        let mut run_bounds = BoundSet::new();
+        run_bounds.insert(Bound::SquaresShapes);
+        let families = solve_squares(
+            nums,
+            goal,
+            options,
+            memo,
+            callback,
+            best_score,
+            &mut run_bounds,
+        )?;
+        run_bounds.extend(memo.bounds_for(goal));
+        let mut bounds_hit = BoundSet::new();
+        for bound in run_bounds.iter() {
+            bounds_hit.insert(match bound {
+                Bound::Depth => Bound::SquaresDepth,
+                bound => bound,
+            });
+        }
+        Ok(StrategyResult {
+            bounds_hit,
+            families,
+        })
+    }
+}
+
+fn solve_squares(
+    nums: &[(u8, f64)],
+    goal: f64,
+    options: &SolveOptions,
+    memo: &mut Memo,
+    callback: impl Fn(Score, &Atom),
+    best_score: &mut u8,
+    bounds_hit: &mut BoundSet,
+) -> Result<Vec<Family>, SolveError> {
+    let mut families = Vec::new();
+    // at least one number is left for the power of 2
+    let combinations = (1..=2)
+        .rev()
+        .filter(|split| *split < nums.len())
+        .flat_map(|split| combinations_when_split(nums, split));
+
+    for (goal_nums, power_nums) in combinations {
+        for (solution, family) in solve_square(
+            &goal_nums,
+            &power_nums,
+            goal,
+            options,
+            AtomFilter::MinScore(*best_score),
+            memo,
+            bounds_hit,
+        )? {
+            if let Some((score, atom)) = solution {
+                *best_score = score.score();
+                callback(score, &atom);
+            }
+            families.extend(family);
+        }
+    }
+    // instances built from different goal solutions can grow into the same
+    // largest instance, which is then only reported with its best found score
+    families.sort_by_key(|family| Reverse(family.score.score()));
+    let mut seen = HashSet::new();
+    families.retain(|family| seen.insert(family.largest_atom.to_string()));
+    Ok(families)
+}
+
+const POWER_OF_2: [f64; 30] = [
+    1., 2., 4., 8., 16., 32., 64., 128., 256., 512., 1024., 2048., 4096., 8192., 16384., 32768.,
+    65536., 131072., 262144., 524288., 1048576., 2097152., 4194304., 8388608., 16777216.,
+    33554432., 67108864., 134217728., 268435456., 536870912.,
+];
+
+// a solution above the score filter, and the family its instance starts, either
+// of which can be missing
+type SquareResult = (Option<(Score, Atom)>, Option<Family>);
+
+fn solve_square<'a>(
+    goal_nums: &[(u8, f64)],
+    power_nums: &'a [(u8, f64)],
+    goal: f64,
+    options: &SolveOptions,
+    mut atom_filter: AtomFilter,
+    memo: &'a mut Memo,
+    // the limits this search hits, since some depend on the goal
+    bounds_hit: &'a mut BoundSet,
+) -> Result<impl Iterator<Item = SquareResult> + 'a, SolveError> {
+    let squares_depth = Depth::both(options.search.squares_depth);
+    let max_square_roots = options.search.max_square_roots;
+    let rules = options.rules;
+    let goal_joiners = get_joiners(goal_nums.len())?;
+    let mut power_joiners = get_joiners(power_nums.len())?;
+
+    let goal_solutions = goal_joiners
+        .into_iter()
+        .flat_map(|mut joiner| {
+            joiner
+                .solve(
+                    goal_nums,
+                    goal,
+                    squares_depth,
+                    rules,
+                    AtomFilter::None,
+                    memo,
+                )
+                .collect_vec()
+        })
+        .collect_vec();
+    // the power of 2 expressions for each count of square roots past the cap,
+    // only solved for once however many instances are extended with them
+    let mut larger_powers: HashMap<usize, Vec<Atom>> = HashMap::new();
+    Ok(goal_solutions
+        .into_iter()
+        .flat_map(move |(_goal_score, goal_atom)| {
+            let goal_atom_steps = goal_atom.get_steps_with_eval();
+            let (inner_goal, max_step) = goal_atom_steps
+                .into_iter()
+                .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+                .unwrap();
+            let (goal_atom_outer, goal_atom_inner) = goal_atom.split(max_step);
+            // the inner square roots go on the inner atom's own functions, in one FuncList
+            let max_square_roots =
+                max_square_roots.min(func_list::CAPACITY - goal_atom_inner.funcs.len() + 1);
+            let (max_inner_sqrt, n) = max_func_applications(inner_goal, Func::SquareRoot, false);
+            let max_outer_sqrt =
+                max_func_applications(n, Func::SquareRoot, true).0 - max_inner_sqrt;
+            let capped = max_inner_sqrt + max_outer_sqrt > max_square_roots;
+            if capped {
+                bounds_hit.insert(Bound::SquareRootCap);
+            }
+            let max_sqrt = (max_inner_sqrt + max_outer_sqrt).min(max_square_roots);
+            (1..max_sqrt)
+                .rev()
+                .flat_map(|sqrt_count| {
+                    let power_of_2 = POWER_OF_2[sqrt_count];
+                    // let inner_sqrt = max_inner_sqrt.min(sqrt_count);
+                    // let outer_sqrt = sqrt_count - inner_sqrt;
+                    let outer_sqrt = max_outer_sqrt.min(sqrt_count);
+                    let inner_sqrt = sqrt_count - outer_sqrt;
+                    let mut joiner_solutions = power_joiners
+                        .iter_mut()
+                        .flat_map(|joiner| {
+                            joiner
+                                .solve(
+                                    power_nums,
+                                    power_of_2,
+                                    squares_depth,
+                                    rules,
+                                    AtomFilter::None,
+                                    memo,
+                                )
+                                .collect_vec()
+                        })
+                        .take(SQUARES_TAKE + 1)
+                        .collect_vec();
+                    bounds_hit.extend(memo.bounds_for(power_of_2));
+                    if joiner_solutions.len() > SQUARES_TAKE {
+                        bounds_hit.insert(Bound::SquaresTake);
+                        joiner_solutions.truncate(SQUARES_TAKE);
+                    }
+                    joiner_solutions
+                        .iter()
+                        .cloned()
+                        .filter_map(|(_power_score, power_atom)| {
+                            let atom = build_square(
+                                &goal_atom_outer,
+                                &goal_atom_inner,
+                                power_atom,
+                                inner_sqrt,
+                                outer_sqrt,
+                            );
+                            let score = atom.get_score();
+                            // only the most square roots can be extended, and only
+                            // on the inner side once the outer side is full. this is
+                            // checked before the score filter, since a larger
+                            // instance can outscore the best solution when this one
+                            // doesn't
+                            let extendable = capped
+                                && sqrt_count == max_sqrt - 1
+                                && outer_sqrt == max_outer_sqrt
+                                && inner_sqrt > 0;
+                            let above_min = match atom_filter {
+                                AtomFilter::MinScore(min_score) => score.score() > min_score,
+                                AtomFilter::None => true,
+                            };
+                            if !above_min && !extendable {
+                                return None;
+                            }
+                            if !test_square(&atom, goal) {
+                                return None;
+                            }
+                            let family = if extendable {
+                                Family::detect(score, &atom, sqrt_count, |square_roots| {
+                                    // the extra square roots all go on the inner side,
+                                    // which has to hold them in one FuncList
+                                    let inner_sqrt = square_roots - outer_sqrt;
+                                    if goal_atom_inner.funcs.len() + inner_sqrt
+                                        > func_list::CAPACITY
+                                    {
+                                        return None;
+                                    }
+                                    let power_atoms =
+                                        larger_powers.entry(square_roots).or_insert_with(|| {
+                                            let power_of_2 = 2f64.powi(square_roots as i32);
+                                            power_joiners
+                                                .iter_mut()
+                                                .flat_map(|joiner| {
+                                                    joiner
+                                                        .solve(
+                                                            power_nums,
+                                                            power_of_2,
+                                                            squares_depth,
+                                                            rules,
+                                                            AtomFilter::None,
+                                                            memo,
+                                                        )
+                                                        .map(|(_power_score, power_atom)| {
+                                                            power_atom
+                                                        })
+                                                        .collect_vec()
+                                                })
+                                                .collect_vec()
+                                        });
+                                    power_atoms
+                                        .iter()
+                                        .map(|power_atom| {
+                                            build_square(
+                                                &goal_atom_outer,
+                                                &goal_atom_inner,
+                                                power_atom.clone(),
+                                                inner_sqrt,
+                                                outer_sqrt,
+                                            )
+                                        })
+                                        .find(|atom| test_square(atom, goal))
+                                })
+                            } else {
+                                None
+                            };
+                            if !above_min {
+                                return family.map(|family| (None, Some(family)));
+                            }
+                            if let AtomFilter::MinScore(min_score) = &mut atom_filter {
+                                *min_score = score.score();
+                            }
+                            Some((Some((score, atom)), family))
+                        })
+                        .collect_vec()
+                })
+                .collect_vec()
+        }))
+}
+
+// (x ^ 2^n) with inner_sqrt square roots on x and outer_sqrt on the power,
+// in the hole the goal atom was split at
+fn build_square(
+    goal_atom_outer: &Atom,
+    goal_atom_inner: &Atom,
+    power_atom: Atom,
+    inner_sqrt: usize,
+    outer_sqrt: usize,
+) -> Atom {
+    let mut goal_atom_inner = goal_atom_inner.clone();
+    for _ in 0..inner_sqrt {
+        goal_atom_inner.funcs.push(Func::SquareRoot);
+    }
+    let mut inner_goal_atom = Atom::new_express(goal_atom_inner, power_atom, Operation::Power);
+    for _ in 0..outer_sqrt {
+        inner_goal_atom.funcs.push(Func::SquareRoot);
+    }
+    let mut atom = goal_atom_outer.clone();
+    atom.fill_hole(inner_goal_atom);
+    atom
+}
+
+fn test_square(atom: &Atom, goal: f64) -> bool {
+    let accepted = atom.test(goal);
+    stats::record(|stats| {
+        stats.tested += 1;
+        stats.accepted += accepted as u64;
+    });
+    accepted
+}
+
+pub fn max_func_applications(mut num: f64, func: Func, rev: bool) -> (usize, f64) {
+    let mut count = 0;
+    while count < 100 {
+        let new_num = func.apply_rev_if(num, rev);
+        match new_num {
+            Some(new_num) => num = new_num,
+            None => return (count, num),
+        }
+        count += 1;
+    }
+    (count, num)
+}
+
+type Split = (Vec<(u8, f64)>, Vec<(u8, f64)>);
+pub fn combinations_when_split(
+    nums: &[(u8, f64)],
+    split: usize,
+) -> impl Iterator<Item = Split> + '_ {
+    fn rec(len: usize, split: usize) -> impl Iterator<Item = Vec<usize>> {
+        if split == 1 {
+            return (0..len).map(|x| vec![x]).collect_vec().into_iter();
+        }
+        let mut all_chosen: Vec<Vec<usize>> = Vec::new();
+        for i in 0..(len - split + 1) {
+            all_chosen.extend(rec(len - i - 1, split - 1).map(|x| {
+                std::iter::once(i)
+                    .chain(x.iter().map(|y| y + i + 1))
+                    .collect_vec()
+            }));
+        }
+        all_chosen.into_iter()
+    }
+    rec(nums.len(), split).map(move |chosen| {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for (i, num) in nums.iter().enumerate() {
+            if chosen.contains(&i) {
+                left.push(*num);
+            } else {
+                right.push(*num);
+            }
+        }
+        (left, right)
+    })
+}
@@ -1,11 +1,17 @@
+use crate::error::SolveError;
 use itertools::{iproduct, Itertools};
 use strum::IntoEnumIterator;
+use tracing::trace;
 
+use super::bounds::{Bound, BoundSet};
 use super::func::Func;
 use super::func_list::FuncList;
 use super::joiner::Memo;
 use super::operation::Operation;
+use super::options::Depth;
+use super::rules::Rules;
 use super::score::Score;
+use super::stats;
 
 #[derive(Debug, Clone)]
 pub enum Path {
@@ -40,7 +46,7 @@ impl Val {
         Self {
             num,
             origin: self.num,
-            score: self.score.add_funcs_list(funcs.clone()),
+            score: self.score.add_funcs_list(funcs),
             funcs,
             path: self.path.clone(),
         }
@@ -99,10 +105,12 @@ impl Arena {
     pub fn get(&self, index: usize) -> &Node {
         &self.nodes[index]
     }
-    pub fn from_string(s: &str) -> Arena {
+    pub fn from_string(s: &str) -> Result<Arena, SolveError> {
         let mut arena = Arena::new();
         let mut lines = s.lines().filter(|line| !line.trim().is_empty()).step_by(2);
-        let first_line = lines.next().unwrap();
+        let first_line = lines
+            .next()
+            .ok_or_else(|| SolveError::InvalidTree(s.to_string()))?;
         let mut nodes = vec![arena.add_new(
             if first_line.contains("H") || first_line.contains("G") {
                 Kind::Goal
@@ -115,26 +123,26 @@ impl Arena {
         for line in lines {
             let mut new_nodes = vec![];
             for (i, (left, right)) in line.split_ascii_whitespace().tuples().enumerate() {
-                let mut each_child = |child: &str| -> usize {
+                let mut each_child = |child: &str| -> Result<usize, SolveError> {
                     let id = arena.add_new(
                         match child {
                             "G" | "H" => Kind::Goal,
                             "N" | "O" => Kind::Num,
-                            _ => panic!("invalid char"),
+                            _ => return Err(SolveError::InvalidTree(s.to_string())),
                         },
                         Some(nodes[i]),
                     );
                     if child == "H" || child == "O" {
                         new_nodes.push(id);
                     }
-                    id
+                    Ok(id)
                 };
 
-                arena.get_mut(nodes[i]).link = Link::Branch(each_child(left), each_child(right));
+                arena.get_mut(nodes[i]).link = Link::Branch(each_child(left)?, each_child(right)?);
             }
             nodes = new_nodes;
         }
-        arena
+        Ok(arena)
     }
     pub fn perm_map(&self) -> Vec<bool> {
         let mut map = Vec::new();
@@ -161,7 +169,7 @@ impl Arena {
         }
         map
     }
-    pub fn populate(&mut self, nums: &[(u8, f64)], goal: Option<f64>, memo: &Memo) {
+    pub fn populate(&mut self, nums: &[(u8, f64)], goal: Option<f64>) {
         self.keys = vec!["".to_string(); self.nodes.len()];
         for (i, (id, _)) in self
             .nodes
@@ -184,10 +192,10 @@ impl Arena {
             self.keys[goal_id] = goal_key(goal);
         }
         self.keys = (0..self.nodes.len())
-            .map(|id| self.init_node_key(id, memo))
+            .map(|id| self.init_node_key(id))
             .collect();
     }
-    fn init_node_key(&self, id: usize, memo: &Memo) -> String {
+    fn init_node_key(&self, id: usize) -> String {
         let node = self.get(id);
         match node.link {
             Link::Leaf => {
@@ -195,8 +203,8 @@ impl Arena {
                 self.keys[id].clone()
             }
             Link::Branch(left, right) => {
-                let left_key = self.init_node_key(left, memo);
-                let right_key = self.init_node_key(right, memo);
+                let left_key = self.init_node_key(left);
+                let right_key = self.init_node_key(right);
                 let kind = match node.kind {
                     Kind::Num => "N",
                     Kind::Goal => "G",
@@ -213,7 +221,7 @@ impl Arena {
     }
     pub fn get_vals_from_memo<'a>(&self, id: usize, memo: &'a Memo) -> &'a [Val] {
         let key = &self.keys[id];
-        assert!(key.len() > 0);
+        assert!(!key.is_empty());
         if let Some(vals) = memo.get(key) {
             vals
         } else {
@@ -225,35 +233,53 @@ impl Arena {
         let key = &self.keys[id];
         memo.insert(key.clone(), vals);
     }
-    pub fn solve(&self, depth: usize, memo: &mut Memo) {
-        fn rec(arena: &Arena, id: usize, depth: usize, memo: &mut Memo) {
+    pub fn solve(&self, depth: Depth, rules: Rules, memo: &mut Memo) {
+        fn rec(arena: &Arena, id: usize, depth: Depth, rules: Rules, memo: &mut Memo) {
             let node = arena.get(id);
             // check if in memo
-            if let Some(_) = memo.get(&arena.keys[id]) {
+            if memo.get(&arena.keys[id]).is_some() {
+                trace!(key = %arena.keys[id], "memo hit");
+                stats::record(|stats| stats.memo_hits += 1);
                 // if this node is calculated, children must be calculated
                 return;
             }
+            trace!(key = %arena.keys[id], "memo miss");
             let mut vals = Vec::new();
             if let Link::Branch(left_id, right_id) = node.link {
-                rec(arena, left_id, depth, memo);
-                rec(arena, right_id, depth, memo);
-                vals.extend(expand_node(arena, left_id, right_id, memo));
+                rec(arena, left_id, depth, rules, memo);
+                rec(arena, right_id, depth, rules, memo);
+                vals.extend(expand_node(arena, left_id, right_id, rules, memo));
             }
+            let is_goal = matches!(node.kind, Kind::Goal);
             for i in 0..vals.len() {
                 let val = vals[i].clone();
+                let (paths, bounds_hit) =
+                    expand_funcs(val.num, is_goal, depth.for_side(is_goal), rules);
+                if is_goal {
+                    memo.extend_goal_bounds(&arena.keys[arena.get_goal_id()], bounds_hit);
+                } else {
+                    memo.bounds.extend(bounds_hit);
+                }
                 vals.extend(
-                    expand_funcs(val.num, matches!(node.kind, Kind::Goal), depth)
+                    paths
                         .into_iter()
                         .map(|(num, funcs)| val.clone_with_funcs(num, funcs)),
                 );
             }
+            stats::record(|stats| {
+                stats.memo_misses += 1;
+                stats.add_key_vals(&arena.keys[id], vals.len() as u64);
+            });
             arena.set_vals_in_memo(id, vals, memo);
         }
-        rec(self, 0, depth, memo);
+        rec(self, 0, depth, rules, memo);
     }
     pub fn len(&self) -> usize {
         self.nodes.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
     // pub fn count_num_leaves(&self) -> usize {
     //     self.nodes
     //         .iter()
@@ -277,39 +303,67 @@ pub fn num_key(num: f64, tag: u8) -> String {
 pub fn goal_key(goal: f64) -> String {
     format!("G {}", goal)
 }
-pub fn expand_funcs(start: f64, reverse: bool, depth: usize) -> Vec<(f64, FuncList)> {
+// also returns the bounds that cut off paths: the depth, for paths that could
+// have kept going, and non-integer values, which are thrown away
+pub fn expand_funcs(
+    start: f64,
+    reverse: bool,
+    depth: usize,
+    rules: Rules,
+) -> (Vec<(f64, FuncList)>, BoundSet) {
+    fn expand_path(
+        (num, funcs): &(f64, FuncList),
+        reverse: bool,
+        rules: Rules,
+    ) -> impl Iterator<Item = (f64, FuncList)> + '_ {
+        Func::iter()
+            .filter(move |func| rules.allows_func(func))
+            .filter_map(move |func| {
+                func.apply_rev_if(*num, reverse).map(|num| {
+                    let mut new_funcs = *funcs;
+                    new_funcs.push(func);
+                    (num, new_funcs)
+                })
+            })
+    }
+    // TODO remove this
+    let is_integer = |(num, _): &(f64, FuncList)| num.fract() == 0.0;
     let mut paths: Vec<(f64, FuncList)> = vec![(start, FuncList::new())];
     let mut high_paths_start = 0;
+    let mut bounds_hit = BoundSet::new();
+    let mut depth_limited = true;
 
     for _ in 0..=depth {
-        let new_paths: Vec<_> = paths[high_paths_start..]
+        let (new_paths, non_integers): (Vec<_>, Vec<_>) = paths[high_paths_start..]
             .iter()
-            .flat_map(|(num, funcs)| {
-                Func::iter()
-                    .filter_map(|func| {
-                        func.apply_rev_if(*num, reverse).map(|num| {
-                            let mut new_funcs = funcs.clone();
-                            new_funcs.push(func);
-                            (num, new_funcs)
-                        })
-                    })
-                    .filter(|(num, _)| num.fract() == 0.0) // TODO remove this
-            })
-            .collect();
-        if new_paths.len() == 0 {
+            .flat_map(|path| expand_path(path, reverse, rules))
+            .partition(is_integer);
+        if !non_integers.is_empty() {
+            bounds_hit.insert(Bound::NonInteger);
+        }
+        if new_paths.is_empty() {
+            depth_limited = false;
             break;
         }
         high_paths_start = paths.len();
         paths.extend(new_paths);
     }
+    if depth_limited
+        && paths[high_paths_start..]
+            .iter()
+            .any(|path| expand_path(path, reverse, rules).any(|path| is_integer(&path)))
+    {
+        bounds_hit.insert(Bound::Depth);
+    }
     paths.swap_remove(0);
-    paths
+    (paths, bounds_hit)
 }
 
 fn expand_node<'a>(
     arena: &'a Arena,
     left_id: usize,
     right_id: usize,
+    rules: Rules,
     memo: &'a Memo,
 ) -> impl Iterator<Item = Val> + 'a {
     let left_node = arena.get(left_id);
@@ -319,14 +373,13 @@ fn expand_node<'a>(
         arena.get_vals_from_memo(left_id, memo).iter().enumerate(),
         arena.get_vals_from_memo(right_id, memo).iter().enumerate()
     )
-    .flat_map(|((left_i, left), (right_i, right))| {
+    .flat_map(move |((left_i, left), (right_i, right))| {
         match (&left_node.kind, &right_node.kind) {
-            (Kind::Num, Kind::Num) => Operation::apply_all(left.num, right.num, false),
-            (Kind::Num, Kind::Goal) => Operation::apply_all(left.num, right.num, true),
-            (Kind::Goal, Kind::Num) => Operation::apply_all(right.num, left.num, true),
+            (Kind::Num, Kind::Num) => Operation::apply_all(left.num, right.num, false, rules),
+            (Kind::Num, Kind::Goal) => Operation::apply_all(left.num, right.num, true, rules),
+            (Kind::Goal, Kind::Num) => Operation::apply_all(right.num, left.num, true, rules),
             _ => panic!("two goals in one tree"),
         }
-        .into_iter()
         .map(move |(op, num)| Val {
             num,
             origin: num,
@@ -0,0 +1,128 @@
+use crate::error::SolveError;
+use crate::finder::options::SolveOptions;
+use crate::finder::{best_solution_with_count, Solution};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::{Display, Formatter};
+
+// random candidates tried for each puzzle before giving up
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Difficulty {
+    pub min_score: u8,
+    pub max_score: Option<u8>,
+    // different expressions that hit the goal, fewer makes a harder puzzle
+    pub min_solutions: usize,
+    pub max_solutions: Option<usize>,
+}
+
+impl Difficulty {
+    fn allows_score(&self, score: u8) -> bool {
+        score >= self.min_score && self.max_score.is_none_or(|max| score <= max)
+    }
+    // how many solutions to count before the exact number stops mattering
+    fn solution_limit(&self) -> usize {
+        self.max_solutions
+            .map_or(self.min_solutions, |max| max + 1)
+            .max(1)
+    }
+    fn allows_solutions(&self, solutions: usize) -> bool {
+        solutions >= self.min_solutions && self.max_solutions.is_none_or(|max| solutions <= max)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub goal: f64,
+    pub nums: Vec<f64>,
+    pub best: Solution,
+    // capped at the difficulty's limit
+    pub solutions: usize,
+}
+
+pub struct Generator {
+    rng: StdRng,
+    difficulty: Difficulty,
+    options: SolveOptions,
+}
+
+impl Generator {
+    pub fn new(seed: u64, difficulty: Difficulty, options: SolveOptions) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            difficulty,
+            options,
+        }
+    }
+    // None if no candidate matched the difficulty
+    pub fn next_puzzle(&mut self) -> Result<Option<Puzzle>, SolveError> {
+        for _ in 0..MAX_ATTEMPTS {
+            let nums = random_nums(&mut self.rng);
+            let goal = random_goal(&mut self.rng);
+            if let Some(puzzle) = self.check(nums, goal)? {
+                return Ok(Some(puzzle));
+            }
+        }
+        Ok(None)
+    }
+    fn check(&self, nums: Vec<f64>, goal: f64) -> Result<Option<Puzzle>, SolveError> {
+        let (best, solutions) =
+            best_solution_with_count(&nums, goal, &self.options, self.difficulty.solution_limit())?;
+        let Some(best) = best else {
+            return Ok(None);
+        };
+        if !self.difficulty.allows_solutions(solutions) {
+            return Ok(None);
+        }
+        if !self.difficulty.allows_score(best.score.score()) {
+            return Ok(None);
+        }
+        Ok(Some(Puzzle {
+            goal,
+            nums,
+            best,
+            solutions,
+        }))
+    }
+}
+
+// five different numbers from -20 to 20 without 0, a third of them negative,
+// like the date generated ones
+fn random_nums(rng: &mut impl Rng) -> Vec<f64> {
+    let mut nums = Vec::new();
+    while nums.len() < 5 {
+        let mut num = rng.gen_range(1..=20) as f64;
+        if rng.gen_ratio(1, 3) {
+            num = -num;
+        }
+        if !nums.contains(&num) {
+            nums.push(num);
+        }
+    }
+    nums.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    nums
+}
+
+// the goal is the day of the month
+fn random_goal(rng: &mut impl Rng) -> f64 {
+    rng.gen_range(1..=31) as f64
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "goal {} nums \"{}\", best score {}, {} solutions: {}",
+            self.goal,
+            self.nums
+                .iter()
+                .map(|num| num.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            self.best.score,
+            self.solutions,
+            self.best.atom
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::SolveError;
+use crate::finder::atom::{Atom, Val};
+use crate::finder::func::Func;
+use crate::finder::math::within_error;
+use crate::finder::operation::Operation;
+use crate::finder::options::SolveOptions;
+use crate::finder::{best_solution, Solution};
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+
+pub const MAX_HINT_LEVEL: usize = 5;
+
+// from weakest to strongest, each one gives away more of the solution
+#[derive(Debug, Clone)]
+pub enum Hint {
+    // the numbers that are used, in case not all of them are
+    Numbers(Vec<f64>),
+    // the operation that is evaluated first
+    FirstOperation(Operation),
+    // how often each function is applied
+    Functions(Vec<(Func, usize)>),
+    // a value the solution passes through on the way to the goal
+    Intermediate(f64),
+    // the outside of the expression, with a hole where the intermediate value
+    // goes, and the shape of what fills the hole without its numbers
+    Outline { outer: Atom, inner: Atom },
+}
+
+// None if the puzzle has no solution. hints stop early for solutions
+// too simple to have them, e.g. one without any functions
+pub fn get_hints(
+    nums: &[f64],
+    goal: f64,
+    options: &SolveOptions,
+    level: usize,
+) -> Result<Option<Vec<Hint>>, SolveError> {
+    let solution = best_solution(nums, goal, options)?;
+    Ok(solution.map(|solution| hints_for(&solution).into_iter().take(level).collect()))
+}
+
+pub fn hints_for(solution: &Solution) -> Vec<Hint> {
+    let atom = &solution.atom;
+    let mut hints = vec![Hint::Numbers(atom.nums())];
+    if let Some(op) = first_operation(atom) {
+        hints.push(Hint::FirstOperation(op));
+    }
+    let funcs = used_funcs(atom);
+    if !funcs.is_empty() {
+        hints.push(Hint::Functions(funcs));
+    }
+    if let Some((intermediate, step)) = intermediate_step(solution) {
+        hints.push(Hint::Intermediate(intermediate));
+        let (outer, inner) = atom.clone().split(step);
+        hints.push(Hint::Outline {
+            outer,
+            inner: inner.with_holes(),
+        });
+    }
+    hints
+}
+
+// evaluation goes left to right, depth first
+fn first_operation(atom: &Atom) -> Option<Operation> {
+    match &atom.val {
+        Val::Express { left, right, op } => first_operation(left)
+            .or_else(|| first_operation(right))
+            .or_else(|| Some(op.clone())),
+        _ => None,
+    }
+}
+
+fn used_funcs(atom: &Atom) -> Vec<(Func, usize)> {
+    fn rec(atom: &Atom, funcs: &mut Vec<Func>) {
+        funcs.extend(atom.funcs.iter());
+        if let Val::Express { left, right, .. } = &atom.val {
+            rec(left, funcs);
+            rec(right, funcs);
+        }
+    }
+    let mut funcs = Vec::new();
+    rec(atom, &mut funcs);
+    funcs
+        .into_iter()
+        .sorted_by_key(|func| func.clone() as u8)
+        .dedup_with_count()
+        .map(|(count, func)| (func, count))
+        .collect()
+}
+
+// the meeting value if the search found it, otherwise the value of the left
+// side of the outermost operation
+fn intermediate_step(solution: &Solution) -> Option<(f64, (usize, usize))> {
+    let steps = solution.atom.get_steps_with_eval();
+    match solution.meeting {
+        // the outermost step is the goal itself, which isn't much of a hint
+        Some(meeting) => steps
+            .into_iter()
+            .find(|(num, (atom_step, _))| *atom_step > 0 && within_error(*num, meeting)),
+        None => match &solution.atom.val {
+            Val::Express { left, .. } => Some((left.value()?, (1, left.funcs.len()))),
+            _ => None,
+        },
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hint::Numbers(nums) => write!(
+                f,
+                "numbers used: {}",
+                nums.iter().map(|num| num.to_string()).join(" ")
+            ),
+            Hint::FirstOperation(op) => write!(f, "first operation: {}", op),
+            Hint::Functions(funcs) => write!(
+                f,
+                "functions: {}",
+                funcs
+                    .iter()
+                    .map(|(func, count)| format!("{} x{}", func, count))
+                    .join(", ")
+            ),
+            Hint::Intermediate(num) => write!(f, "aim for {} along the way", num),
+            Hint::Outline { outer, inner } => {
+                write!(f, "outline: {}, where [hole] is {}", outer, inner)
+            }
+        }
+    }
+}
@@ -1,5 +1,21 @@
-use chrono::Datelike;
-use clap::Parser;
+use crate::config::{Config, OutputFormat};
+use crate::error::InputError;
+use crate::finder::func::Func;
+use crate::finder::math::MAX_NUM_SIZE;
+use crate::finder::options::{
+    Engine, Preset, SearchOptions, SolveOptions, MAX_DEPTH, MAX_SQUARE_ROOTS,
+};
+use crate::finder::rules::{FamilyRanking, OperationGroup, RulePreset, Rules};
+use crate::finder::stop::Stop;
+use crate::finder::strategy::StrategyKind;
+use crate::hints::MAX_HINT_LEVEL;
+use crate::seeding::{Formula, Seeding, SetNumbers};
+use crate::timezone::Zone;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -10,6 +26,15 @@ struct Args {
     /// Goal number (prioritized over date generated numbers)
     #[arg(short, long, allow_hyphen_values = true)]
     goal: Option<f64>,
+    /// Full date to use for generating numbers: 'YYYY-MM-DD', 'today', 'yesterday', 'tomorrow', a day offset like '+3' or '-7', or a weekday name for its most recent day
+    #[arg(short = 'f', long, visible_alias = "date", allow_hyphen_values = true, conflicts_with_all = ["day", "month", "year"])]
+    full_date: Option<String>,
+    /// Config file with another formula for turning dates into puzzles, see seeding.example.toml
+    #[arg(long)]
+    seeding: Option<PathBuf>,
+    /// Timezone whose date is today: an IANA name like 'Europe/Berlin' or a UTC offset like '+05:30' (defaults to the system's)
+    #[arg(long, allow_hyphen_values = true)]
+    tz: Option<String>,
     /// Day of month to use for generating numbers
     #[arg(short, long)]
     day: Option<u32>,
@@ -19,57 +44,352 @@ struct Args {
     /// Year to use for generating numbers
     #[arg(short, long)]
     year: Option<u32>,
+    /// Config file with defaults for these options (defaults to $XDG_CONFIG_HOME/dateo-fun-remover/config.toml)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Output format for solving: 'text' or 'json' (one object per line) [default: text]
+    #[arg(long)]
+    format: Option<OutputFormat>,
+    /// File that every solve appends its result to, as a line of json
+    #[arg(long)]
+    archive: Option<PathBuf>,
+    /// Rule set to solve with: 'standard' or 'basic' (only + - * /) [default: standard]
+    #[arg(short, long)]
+    rules: Option<RulePreset>,
+    /// Operation group to turn off: 'addition', 'multiplication', 'power' or 'root'. Can be repeated
+    #[arg(long)]
+    disable: Vec<OperationGroup>,
+    /// Function to turn off: 'square-root', 'factorial' or 'summation'. Can be repeated
+    #[arg(long)]
+    disable_function: Vec<Func>,
+    /// How squares solutions that stop at the square root cap are ranked: 'found' or 'largest'
+    #[arg(long, default_value = "found")]
+    family_ranking: FamilyRanking,
+    /// How hard to search: 'quick', 'normal' or 'exhaustive' [default: normal]
+    #[arg(short, long)]
+    preset: Option<Preset>,
+    /// Most functions chained onto a number side value, up to 15 (overrides the preset)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=MAX_DEPTH as i64))]
+    num_depth: Option<u8>,
+    /// Most functions chained onto a goal side value, up to 15 (overrides the preset)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=MAX_DEPTH as i64))]
+    goal_depth: Option<u8>,
+    /// Function depth used by the squares trick, up to 15 (overrides the preset)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=MAX_DEPTH as i64))]
+    squares_depth: Option<u8>,
+    /// Most square roots the squares trick will stack (overrides the preset)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=MAX_SQUARE_ROOTS as i64))]
+    max_square_roots: Option<u8>,
+    /// Score inflating strategy to skip: 'squares'. Can be repeated
+    #[arg(long)]
+    skip_strategy: Vec<StrategyKind>,
+    /// Print a solution quickly from a shallow search, then keep refining it
+    #[arg(long)]
+    anytime: bool,
+    /// Search to solve with: 'joiner' or 'legacy' (the older, slower engine, without strategies)
+    #[arg(long, default_value = "joiner")]
+    engine: Engine,
+    /// Print where the solve spent its time and how much work it did (always in json output)
+    #[arg(long)]
+    stats: bool,
+    /// Print what the search is doing to stderr: -v for each joiner, -vv for every permutation and memo lookup
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// File to write a trace of the search to, for chrome://tracing or Perfetto
+    #[arg(long)]
+    chrome_trace: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-type YearMonthDay = (u32, u32, u32);
-fn get_current_date() -> YearMonthDay {
-    let now = chrono::Local::now();
-    (now.year() as u32, now.month(), now.day())
+#[derive(Subcommand)]
+pub enum Command {
+    /// List every integer goal in a range that the numbers can reach, with its best score
+    Reach {
+        /// First goal of the range
+        #[arg(long, allow_hyphen_values = true, default_value_t = 1)]
+        from: i64,
+        /// Last goal of the range (inclusive)
+        #[arg(long, allow_hyphen_values = true, default_value_t = 31)]
+        to: i64,
+    },
+    /// Give hints towards the best solution instead of the solution itself
+    Hint {
+        /// How many hints to give, from 1 (the numbers used) to 5 (an outline of the expression)
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_HINT_LEVEL as i64))]
+        level: u8,
+    },
+    /// Practice on the puzzle: type expressions and compare them with the solver's best
+    Practice,
+    /// List every puzzle that is today somewhere on Earth right now, with the timezones it is today in
+    Today,
+    /// Print the options in effect, from the config file and the flags, as a config file
+    Config,
+    /// Find the dates whose puzzle has the numbers given with --nums, and the goal if one is given, or with --twins dates that share numbers
+    WhichDate {
+        /// First date to search
+        #[arg(long, default_value = "1900-01-01")]
+        from: NaiveDate,
+        /// Last date to search (inclusive)
+        #[arg(long, default_value = "2100-12-31")]
+        to: NaiveDate,
+        /// List every set of numbers that more than one date in the range has
+        #[arg(long)]
+        twins: bool,
+    },
+    /// Serve a local JSON API for solving and checking puzzles, on 127.0.0.1 only
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Most puzzles solved at once, more are turned away until one finishes
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        max_concurrent: u64,
+        /// Seconds a solve may run before it stops with the best solution so far
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Answer JSON-RPC 2.0 requests on stdin, one per line, reusing the solver's memory between them
+    Rpc,
+    /// Re-solve a corpus of puzzles and report any whose best score dropped
+    Check {
+        /// Corpus file, one puzzle per line
+        #[arg(long, default_value = "corpus.txt")]
+        corpus: PathBuf,
+    },
+    /// Solve a corpus of puzzles with both engines and report where they disagree
+    DiffEngines {
+        /// Corpus file, one puzzle per line
+        #[arg(long, default_value = "corpus.txt")]
+        corpus: PathBuf,
+    },
+    /// Generate random puzzles that the solver can solve, filtered by difficulty
+    Generate {
+        /// Seed for the random numbers, a random one is picked and printed if not given
+        #[arg(long)]
+        seed: Option<u64>,
+        /// How many puzzles to generate
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Lowest best score a puzzle may have
+        #[arg(long, default_value_t = 0)]
+        min_score: u8,
+        /// Highest best score a puzzle may have
+        #[arg(long)]
+        max_score: Option<u8>,
+        /// Fewest different solutions a puzzle may have
+        #[arg(long, default_value_t = 1)]
+        min_solutions: usize,
+        /// Most different solutions a puzzle may have
+        #[arg(long)]
+        max_solutions: Option<usize>,
+    },
 }
 
-fn parse_nums(nums: &str) -> Vec<f64> {
-    let ret: Vec<_> = nums.split(" ")
-        .map(|num| num.parse::<f64>().expect("Number parsing error. Please provide a space separated list of numbers, e.g. '1 2 3 4 5'"))
-        .collect();
-    if ret.len() > 5 {
-        panic!("Please provide no more than 5 numbers")
+pub struct Inputs {
+    pub goal: f64,
+    pub nums: Vec<f64>,
+    // false if the goal came from the date
+    pub goal_given: bool,
+    pub nums_given: bool,
+    // the date the goal or numbers were generated from, None if both were given
+    pub date: Option<NaiveDate>,
+    // how dates turn into puzzles
+    pub seeding: Box<dyn Seeding>,
+    // the timezone whose date is today, None for the system's
+    pub zone: Option<Zone>,
+    // the config file merged with the flags, with defaults filled in
+    pub config: Config,
+    // the config file that was read, if any
+    pub config_path: Option<PathBuf>,
+    pub options: SolveOptions,
+    pub command: Option<Command>,
+    // whether to print the solve's stats
+    pub stats: bool,
+    // how many times -v was given
+    pub verbose: u8,
+    // where to write a chrome trace of the run, if anywhere
+    pub chrome_trace: Option<PathBuf>,
+}
+
+pub fn get_current_date(zone: Option<&Zone>) -> NaiveDate {
+    match zone {
+        Some(zone) => zone.date_at(chrono::Utc::now()),
+        None => chrono::Local::now().date_naive(),
+    }
+}
+
+// reads a full date, relative to today for everything but 'YYYY-MM-DD'.
+// weekday names ('monday' or 'mon') mean the most recent one, today included
+pub fn parse_date(date: &str, today: NaiveDate) -> Result<NaiveDate, InputError> {
+    let unknown = || InputError::UnknownDate(date.to_string());
+    // far past any date chrono can hold, but small enough for a Duration
+    let offset = |days: i64| {
+        (days.abs() < 1 << 32)
+            .then(|| today.checked_add_signed(Duration::days(days)))
+            .flatten()
+            .ok_or_else(unknown)
+    };
+    let lower = date.trim().to_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return offset(-1),
+        "tomorrow" => return offset(1),
+        _ => {}
+    }
+    if lower.starts_with(['+', '-']) {
+        return offset(lower.parse().map_err(|_| unknown())?);
     }
-    if ret.len() < 5 {
-        panic!("Please provide at least 5 numbers")
+    if let Ok(weekday) = lower.parse::<Weekday>() {
+        let days_back =
+            (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        return offset(-(days_back as i64));
     }
-    ret
+    NaiveDate::parse_from_str(&lower, "%Y-%m-%d").map_err(|_| unknown())
 }
 
-// based on https://dateo-math-game.com/setNumbers.js
+// fills whatever of the date wasn't given from today
+fn resolve_date(
+    zone: Option<&Zone>,
+    full_date: Option<&str>,
+    day: Option<u32>,
+    month: Option<u32>,
+    year: Option<u32>,
+) -> Result<NaiveDate, InputError> {
+    let today = get_current_date(zone);
+    if let Some(full_date) = full_date {
+        return parse_date(full_date, today);
+    }
+    let year = year.unwrap_or(today.year() as u32);
+    let month = month.unwrap_or(today.month());
+    let day = day.unwrap_or(today.day());
+    NaiveDate::from_ymd_opt(year as i32, month, day).ok_or(InputError::InvalidDate {
+        year,
+        month,
+        day,
+    })
+}
 
-fn guess_goal(date: YearMonthDay) -> f64 {
-    date.2 as f64
+fn parse_nums(nums: &str) -> Result<Vec<f64>, InputError> {
+    let ret = nums
+        .split_whitespace()
+        .map(|num| {
+            num.parse::<f64>()
+                .map_err(|_| InputError::InvalidNumber(num.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_nums(ret)
 }
 
-fn rng(n: f64) -> f64 {
-    (n * 7_f64.powf(5.)) % (2_f64.powf(31.) - 1.)
+// the solver works with 1 to 5 numbers, and a repeated number is tagged apart
+// from the first
+pub fn check_nums(nums: Vec<f64>) -> Result<Vec<f64>, InputError> {
+    if !(1..=5).contains(&nums.len()) {
+        return Err(InputError::WrongNumberCount(nums.len()));
+    }
+    for num in &nums {
+        check_num(*num)?;
+    }
+    Ok(nums)
 }
-fn guess_nums(date: YearMonthDay) -> Vec<f64> {
-    let mut seed = (date.2 as f64) + (100. * date.0 as f64) + (1_000_000. * date.1 as f64);
-    let mut nums = Vec::new();
-    let mut num;
-    while nums.len() < 5 {
-        seed = rng(seed);
-        num = seed % 20. + 1.;
-        if seed % 3. == 0. {
-            num = -num;
-        }
-        if !nums.contains(&num) {
-            nums.push(num);
-        }
+
+fn check_num(num: f64) -> Result<(), InputError> {
+    if num.is_nan() || num.abs() >= MAX_NUM_SIZE {
+        return Err(InputError::OutOfRange(num));
     }
-    nums.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    nums
+    Ok(())
 }
 
-pub fn get_goal_and_nums_from_args() -> (f64, Vec<f64>) {
+pub fn get_inputs_from_args() -> Result<Inputs, InputError> {
     let args = Args::parse();
-    get_goal_and_nums(args.nums, args.goal, args.day, args.month, args.year)
+    // the numbers are what which-date looks for, today's would only find today
+    if matches!(args.command, Some(Command::WhichDate { twins: false, .. })) && args.nums.is_none()
+    {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "which-date needs the numbers to look for, given with --nums, unless --twins is given",
+            )
+            .exit();
+    }
+    let (file_config, config_path) = Config::load(args.config.as_deref())?;
+    let config = file_config
+        .overridden_by(Config {
+            format: args.format,
+            preset: args.preset,
+            tz: args.tz,
+            rules: args.rules,
+            disable: args.disable,
+            disable_functions: args.disable_function,
+            archive: args.archive,
+        })
+        .with_defaults();
+    let zone = config.tz.as_deref().map(str::parse::<Zone>).transpose()?;
+    let date = resolve_date(
+        zone.as_ref(),
+        args.full_date.as_deref(),
+        args.day,
+        args.month,
+        args.year,
+    )?;
+    let seeding: Box<dyn Seeding> = match &args.seeding {
+        Some(path) => Box::new(Formula::load(path)?),
+        None => Box::new(SetNumbers),
+    };
+    let goal_given = args.goal.is_some();
+    let nums_given = args.nums.is_some();
+    let uses_date = args.nums.is_none() || !goal_given;
+    let (goal, nums) = goal_and_nums_for_date(args.nums, args.goal, date, seeding.as_ref())?;
+    let rules = config
+        .disable
+        .iter()
+        .copied()
+        .fold(Rules::from_preset(config.rules.unwrap()), Rules::disable);
+    let rules = config
+        .disable_functions
+        .iter()
+        .cloned()
+        .fold(rules, Rules::disable_func)
+        .with_family_ranking(args.family_ranking);
+    let mut search = SearchOptions::from_preset(config.preset.unwrap());
+    if let Some(num_depth) = args.num_depth {
+        search.depth.num = num_depth as usize;
+    }
+    if let Some(goal_depth) = args.goal_depth {
+        search.depth.goal = goal_depth as usize;
+    }
+    if let Some(squares_depth) = args.squares_depth {
+        search.squares_depth = squares_depth as usize;
+    }
+    if let Some(max_square_roots) = args.max_square_roots {
+        search.max_square_roots = max_square_roots as usize;
+    }
+    Ok(Inputs {
+        goal,
+        nums,
+        goal_given,
+        nums_given,
+        date: uses_date.then_some(date),
+        seeding,
+        zone,
+        config,
+        config_path,
+        options: SolveOptions {
+            rules,
+            search,
+            strategies: StrategyKind::iter()
+                .filter(|kind| !args.skip_strategy.contains(kind))
+                .collect(),
+            anytime: args.anytime,
+            stop: Stop::new(),
+            engine: args.engine,
+            timed: args.stats,
+        },
+        command: args.command,
+        stats: args.stats,
+        verbose: args.verbose,
+        chrome_trace: args.chrome_trace,
+    })
 }
 
 pub fn get_goal_and_nums(
@@ -78,22 +398,37 @@ pub fn get_goal_and_nums(
     day: Option<u32>,
     month: Option<u32>,
     year: Option<u32>,
-) -> (f64, Vec<f64>) {
-    let mut date = match (year, month, day) {
-        (Some(year), Some(month), Some(day)) => (year, month, day),
-        _ => {
-            let (real_year, real_month, real_day) = get_current_date();
-            (
-                year.unwrap_or(real_year),
-                month.unwrap_or(real_month),
-                day.unwrap_or(real_day),
-            )
-        }
-    };
-    // month is zero indexed in javascript
-    date.1 -= 1;
+) -> Result<(f64, Vec<f64>), InputError> {
+    let date = resolve_date(None, None, day, month, year)?;
+    goal_and_nums_for_date(nums, goal, date, &SetNumbers)
+}
+
+pub fn goal_and_nums_for_date(
+    nums: Option<String>,
+    goal: Option<f64>,
+    date: NaiveDate,
+    seeding: &dyn Seeding,
+) -> Result<(f64, Vec<f64>), InputError> {
+    let nums = nums.map(|nums| parse_nums(&nums)).transpose()?;
+    fill_from_date(nums, goal, date, seeding)
+}
 
-    let goal = goal.unwrap_or(guess_goal(date));
-    let nums = nums.map_or_else(|| guess_nums(date), |nums| parse_nums(&nums));
-    (goal, nums)
+// fills in whichever of the goal and numbers wasn't given from the date's puzzle
+pub fn fill_from_date(
+    nums: Option<Vec<f64>>,
+    goal: Option<f64>,
+    date: NaiveDate,
+    seeding: &dyn Seeding,
+) -> Result<(f64, Vec<f64>), InputError> {
+    let unseedable = || InputError::Unseedable(date);
+    let goal = match goal {
+        Some(goal) => goal,
+        None => seeding.goal(date).ok_or_else(unseedable)?,
+    };
+    check_num(goal)?;
+    let nums = match nums {
+        Some(nums) => check_nums(nums)?,
+        None => seeding.nums(date).ok_or_else(unseedable)?,
+    };
+    Ok((goal, nums))
 }
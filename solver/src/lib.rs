@@ -1,2 +1,14 @@
+pub mod archive;
+pub mod config;
+pub mod corpus;
+pub mod error;
 pub mod finder;
+pub mod generator;
+pub mod hints;
 pub mod inputs;
+pub mod parser;
+pub mod practice;
+pub mod rpc;
+pub mod seeding;
+pub mod server;
+pub mod timezone;
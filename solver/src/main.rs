@@ -1,23 +1,447 @@
-mod finder;
-use finder::solve;
-mod inputs;
-use inputs::get_goal_and_nums_from_args;
+use chrono::NaiveDate;
+use dateo_fun_remover::archive::{append_to_archive, ArchiveEntry, Puzzle};
+use dateo_fun_remover::config::{Config, OutputFormat};
+use dateo_fun_remover::corpus::{check_entry, diff_entry, parse_corpus, CorpusEntry};
+use dateo_fun_remover::error::{InputError, SolveError};
+use dateo_fun_remover::finder::options::SolveOptions;
+use dateo_fun_remover::finder::{solve, solve_goals, SolveReport};
+use dateo_fun_remover::generator::{Difficulty, Generator};
+use dateo_fun_remover::hints::get_hints;
+use dateo_fun_remover::inputs::{get_inputs_from_args, goal_and_nums_for_date, Command, Inputs};
+use dateo_fun_remover::practice::run_practice;
+use dateo_fun_remover::rpc::run_rpc;
+use dateo_fun_remover::seeding::{twin_dates, which_dates, Seeding};
+use dateo_fun_remover::server::{serve, ServeOptions};
+use dateo_fun_remover::timezone::todays_everywhere;
+use serde_json::json;
+use std::cell::RefCell;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
 
 fn main() {
-    let (goal, nums) = get_goal_and_nums_from_args();
+    // everything run owns, like the trace's writer, is dropped before exiting
+    let code = run().unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        err.exit_code()
+    });
+    std::process::exit(code);
+}
+
+// the exit code, 0 unless the check found regressions or diff-engines failures
+fn run() -> Result<i32, SolveError> {
+    let Inputs {
+        goal,
+        nums,
+        goal_given,
+        nums_given,
+        date,
+        seeding,
+        zone,
+        config,
+        config_path,
+        options,
+        command,
+        stats,
+        verbose,
+        chrome_trace,
+    } = get_inputs_from_args()?;
+    let _flush = init_tracing(verbose, chrome_trace.as_deref())?;
+
+    let uses_puzzle = !matches!(
+        command,
+        None | Some(
+            Command::Check { .. }
+                | Command::DiffEngines { .. }
+                | Command::Generate { .. }
+                | Command::Today
+                | Command::WhichDate { .. }
+                | Command::Config
+                | Command::Serve { .. }
+                | Command::Rpc
+        )
+    );
+    if let (Some(date), true) = (date, uses_puzzle) {
+        print_date(date);
+    }
+
+    let result = match command {
+        None => {
+            let puzzle = Puzzle { goal, nums, date };
+            let archive = config.archive.as_deref();
+            match config.format.unwrap_or_default() {
+                OutputFormat::Text => run_solve(&puzzle, &options, archive, stats),
+                OutputFormat::Json => run_solve_json(&puzzle, &options, archive),
+            }
+        }
+        Some(Command::Config) => {
+            run_config(&config, config_path);
+            Ok(())
+        }
+        Some(Command::Reach { from, to }) => run_reach(&nums, from, to, &options),
+        Some(Command::Hint { level }) => run_hint(&nums, goal, level as usize, &options),
+        Some(Command::Practice) => {
+            run_practice(nums, goal, options);
+            Ok(())
+        }
+        Some(Command::Today) => Ok(run_today(seeding.as_ref())?),
+        Some(Command::WhichDate { from, to, twins }) => {
+            let nums = nums_given.then_some(&nums[..]);
+            let goal = goal_given.then_some(goal);
+            run_which_date(seeding.as_ref(), nums, goal, from, to, twins);
+            Ok(())
+        }
+        Some(Command::Serve {
+            port,
+            max_concurrent,
+            timeout,
+        }) => {
+            let serve_options = ServeOptions {
+                port,
+                max_concurrent: max_concurrent as usize,
+                timeout: Duration::from_secs(timeout),
+                zone,
+                archive: config.archive,
+            };
+            Ok(serve(&serve_options, &options, seeding.as_ref())?)
+        }
+        Some(Command::Rpc) => {
+            let input = std::io::stdin().lock();
+            run_rpc(
+                input,
+                Box::new(std::io::stdout()),
+                &options,
+                seeding.as_ref(),
+                zone,
+            );
+            Ok(())
+        }
+        Some(Command::Check { corpus }) => return run_check(&corpus),
+        Some(Command::DiffEngines { corpus }) => return run_diff_engines(&corpus),
+        Some(Command::Generate {
+            seed,
+            count,
+            min_score,
+            max_score,
+            min_solutions,
+            max_solutions,
+        }) => {
+            let difficulty = Difficulty {
+                min_score,
+                max_score,
+                min_solutions,
+                max_solutions,
+            };
+            run_generate(seed, count, difficulty, options)
+        }
+    };
+    result.map(|()| 0)
+}
 
+// progress goes to stderr so what's printed on stdout can be parsed. the
+// returned guard finishes the chrome trace when it's dropped
+fn init_tracing(
+    verbose: u8,
+    chrome_trace: Option<&Path>,
+) -> Result<Option<FlushGuard>, InputError> {
+    let level = match verbose {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    // without -v only the messages are printed, like "solving squares"
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .without_time()
+        .with_target(verbose > 0)
+        .with_level(verbose > 0)
+        .with_filter(filter_fn(move |meta| verbose > 0 || meta.is_event()));
+    let (chrome, flush) = match chrome_trace {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| InputError::InvalidFile {
+                path: path.display().to_string(),
+                reason: err.to_string(),
+            })?;
+            let (chrome, flush) = ChromeLayerBuilder::new().writer(file).build();
+            (Some(chrome), Some(flush))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(fmt)
+        .with(chrome)
+        .with(level)
+        .init();
+    Ok(flush)
+}
+
+fn print_date(date: NaiveDate) {
+    println!("date: {}", date.format("%Y-%m-%d (%A)"));
+}
+
+fn print_nums(nums: &[f64]) {
+    println!("nums: {}", nums_string(nums));
+}
+
+// space separated list of numbers
+fn nums_string(nums: &[f64]) -> String {
+    nums.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn run_solve(
+    puzzle: &Puzzle,
+    options: &SolveOptions,
+    archive: Option<&Path>,
+    stats: bool,
+) -> Result<(), SolveError> {
+    let Puzzle { goal, nums, date } = puzzle;
+    if let Some(date) = date {
+        print_date(*date);
+    }
     println!("goal: {}", goal);
-    // print space separated list of numbers
+    print_nums(nums);
+
+    let best = RefCell::new(None);
+    let report = solve(nums, *goal, options, |score, atom| {
+        println!("atom with score {}: {}", score, atom);
+        for step in atom.eval_steps() {
+            println!("{}", step);
+        }
+        *best.borrow_mut() = Some(atom.to_string());
+    })?;
+    if let Some(archive) = archive {
+        archive_result(archive, puzzle, &report, best.into_inner())?;
+    }
+    for strategy in &report.strategies {
+        println!("{}", strategy);
+    }
+    if !report.families.is_empty() {
+        let ranking = options.rules.family_ranking;
+        for family in &report.families {
+            println!("squares family: {}", family);
+        }
+        println!(
+            "best score ranked by {}: {}",
+            ranking,
+            report.ranked_score(ranking)
+        );
+    }
+    if let Some(near_miss) = report.near_miss {
+        println!("{}", near_miss);
+    }
+    println!("{}", report.completeness);
+    if stats {
+        println!("{}", report.stats);
+    }
+    Ok(())
+}
+
+// one json object per line: the puzzle, each better solution, then the report
+fn run_solve_json(
+    puzzle: &Puzzle,
+    options: &SolveOptions,
+    archive: Option<&Path>,
+) -> Result<(), SolveError> {
+    let Puzzle { goal, nums, date } = puzzle;
     println!(
-        "nums: {}",
-        nums.iter()
-            .map(|x| x.to_string())
-            .collect::<Vec<String>>()
-            .join(" ")
+        "{}",
+        json!({ "event": "puzzle", "date": date, "goal": goal, "nums": nums })
     );
+    let best = RefCell::new(None);
+    let report = solve(nums, *goal, options, |score, atom| {
+        println!(
+            "{}",
+            json!({ "event": "solution", "score": score.score(), "atom": atom.to_string() })
+        );
+        *best.borrow_mut() = Some(atom.to_string());
+    })?;
+    let mut done = report.to_json(options.rules.family_ranking);
+    done["event"] = json!("done");
+    println!("{}", done);
+    if let Some(archive) = archive {
+        archive_result(archive, puzzle, &report, best.into_inner())?;
+    }
+    Ok(())
+}
 
-    solve(&nums, goal, |score, atom| {
-        println!("atom with score {}: {}", score, atom);
-        atom.eval_verbose();
-    })
+fn archive_result(
+    archive: &Path,
+    puzzle: &Puzzle,
+    report: &SolveReport,
+    best: Option<String>,
+) -> Result<(), InputError> {
+    append_to_archive(archive, &ArchiveEntry::new(puzzle, report, best))
+}
+
+fn run_config(config: &Config, path: Option<PathBuf>) {
+    match path {
+        Some(path) => println!("# read from {}", path.display()),
+        None => println!("# no config file, everything is from flags and defaults"),
+    }
+    print!("{}", config.to_toml());
+}
+
+fn run_reach(nums: &[f64], from: i64, to: i64, options: &SolveOptions) -> Result<(), SolveError> {
+    print_nums(nums);
+    println!("{:>6} | {:<24} | expression", "goal", "best score");
+    solve_goals(
+        nums,
+        (from..=to).map(|goal| goal as f64),
+        options,
+        |goal, best| match best {
+            Some((score, atom)) => {
+                println!("{:>6} | {:<24} | {}", goal, score.to_string(), atom)
+            }
+            None => println!("{:>6} | {:<24} |", goal, "unreachable"),
+        },
+    )
+}
+
+fn run_hint(
+    nums: &[f64],
+    goal: f64,
+    level: usize,
+    options: &SolveOptions,
+) -> Result<(), SolveError> {
+    println!("goal: {}", goal);
+    print_nums(nums);
+    match get_hints(nums, goal, options, level)? {
+        Some(hints) => {
+            for (i, hint) in hints.iter().enumerate() {
+                println!("hint {}: {}", i + 1, hint);
+            }
+        }
+        None => println!("no solution found, so there is nothing to hint at"),
+    }
+    Ok(())
+}
+
+fn run_today(seeding: &dyn Seeding) -> Result<(), InputError> {
+    for today in todays_everywhere(chrono::Utc::now()) {
+        let (goal, nums) = goal_and_nums_for_date(None, None, today.date, seeding)?;
+        println!("{}: goal {}, nums {}", today.date, goal, nums_string(&nums));
+        for (offset, zones) in &today.zones {
+            let names = zones.iter().map(|tz| tz.name()).collect::<Vec<_>>();
+            println!("  UTC{}: {}", offset, names.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn run_which_date(
+    seeding: &dyn Seeding,
+    nums: Option<&[f64]>,
+    goal: Option<f64>,
+    from: NaiveDate,
+    to: NaiveDate,
+    twins: bool,
+) {
+    // only --twins can be given without the numbers
+    if let Some(nums) = nums {
+        print_nums(nums);
+        if let Some(goal) = goal {
+            println!("goal: {}", goal);
+        }
+        let dates = which_dates(seeding, nums, goal, from, to);
+        for date in &dates {
+            let goal = seeding.goal(*date).unwrap();
+            println!("{} ({}): goal {}", date, date.format("%A"), goal);
+        }
+        println!("matching dates from {} to {}: {}", from, to, dates.len());
+        if dates.len() > 1 {
+            println!("these dates are twins, they all have the same numbers");
+        }
+    }
+    if twins {
+        let twins = twin_dates(seeding, from, to);
+        for (nums, dates) in &twins {
+            let dates = dates
+                .iter()
+                .map(|date| date.to_string())
+                .collect::<Vec<_>>();
+            println!(
+                "twins with nums {}: {}",
+                nums_string(nums),
+                dates.join(", ")
+            );
+        }
+        println!(
+            "sets of numbers shared by more than one date: {}",
+            twins.len()
+        );
+    }
+}
+
+fn read_corpus(path: &Path) -> Result<Vec<CorpusEntry>, InputError> {
+    let invalid_file = |reason: String| InputError::InvalidFile {
+        path: path.display().to_string(),
+        reason,
+    };
+    let corpus = std::fs::read_to_string(path).map_err(|err| invalid_file(err.to_string()))?;
+    parse_corpus(&corpus).map_err(invalid_file)
+}
+
+fn run_check(path: &Path) -> Result<i32, SolveError> {
+    let entries = read_corpus(path)?;
+    let mut regressions = 0;
+    for entry in &entries {
+        let result = check_entry(entry)?;
+        if result.is_regression() {
+            regressions += 1;
+        }
+        println!("{}", result);
+    }
+    println!("{} of {} puzzles regressed", regressions, entries.len());
+    Ok(if regressions > 0 { 1 } else { 0 })
+}
+
+fn run_diff_engines(path: &Path) -> Result<i32, SolveError> {
+    let entries = read_corpus(path)?;
+    let mut disagreements = 0;
+    let mut failures = 0;
+    for entry in &entries {
+        let result = diff_entry(entry)?;
+        if result.is_disagreement() {
+            disagreements += 1;
+        }
+        if result.is_failure() {
+            failures += 1;
+        }
+        println!("{}", result);
+    }
+    println!(
+        "the engines disagreed on {} of {} puzzles, {} of them failures",
+        disagreements,
+        entries.len(),
+        failures
+    );
+    Ok(if failures > 0 { 1 } else { 0 })
+}
+
+fn run_generate(
+    seed: Option<u64>,
+    count: usize,
+    difficulty: Difficulty,
+    options: SolveOptions,
+) -> Result<(), SolveError> {
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("seed: {}", seed);
+    let mut generator = Generator::new(seed, difficulty, options);
+    for _ in 0..count {
+        match generator.next_puzzle()? {
+            Some(puzzle) => println!("puzzle: {}", puzzle),
+            None => {
+                println!("no puzzle found with that difficulty");
+                break;
+            }
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,239 @@
+use crate::finder::atom::Atom;
+use crate::finder::func::Func;
+use crate::finder::operation::Operation;
+use std::fmt::{Display, Formatter};
+
+// reads expressions in the same notation atoms are printed in, e.g.
+// "²√((Σ11 - (-18 * (-2 + ²√9))) - 20)", with ascii names for the symbols:
+// sqrt for ²√, sum for Σ and root for √. prefix functions apply before a
+// trailing !, so "²√9!" is (²√9)!, like atoms print it
+pub fn parse_atom(input: &str) -> Result<Atom, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let atom = parser.expr()?;
+    match parser.peek() {
+        None => Ok(atom),
+        Some(token) => Err(parser.error(format!("unexpected {}", token))),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // index of the token the error was found at
+    pub token: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at token {})", self.message, self.token + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Power,
+    Root,
+    SquareRoot,
+    Summation,
+    Factorial,
+    Open,
+    Close,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Times => write!(f, "*"),
+            Token::Divide => write!(f, "/"),
+            Token::Power => write!(f, "^"),
+            Token::Root => write!(f, "√"),
+            Token::SquareRoot => write!(f, "²√"),
+            Token::Summation => write!(f, "Σ"),
+            Token::Factorial => write!(f, "!"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' | '×' => Token::Times,
+            '/' | '÷' => Token::Divide,
+            '^' => Token::Power,
+            '√' => Token::Root,
+            '!' => Token::Factorial,
+            'Σ' => Token::Summation,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '²' => match chars.next() {
+                Some('√') => Token::SquareRoot,
+                _ => return Err(tokenize_error(&tokens, "expected √ after ²")),
+            },
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut num = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    num.push(c);
+                }
+                match num.parse() {
+                    Ok(num) => Token::Num(num),
+                    Err(_) => return Err(tokenize_error(&tokens, &format!("bad number {}", num))),
+                }
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "sqrt" => Token::SquareRoot,
+                    "sum" => Token::Summation,
+                    "root" => Token::Root,
+                    _ => return Err(tokenize_error(&tokens, &format!("unknown name {}", word))),
+                }
+            }
+            c => return Err(tokenize_error(&tokens, &format!("unexpected {}", c))),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn tokenize_error(tokens: &[Token], message: &str) -> ParseError {
+    ParseError {
+        token: tokens.len(),
+        message: message.to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            token: self.pos,
+            message,
+        }
+    }
+    // + and -
+    fn expr(&mut self) -> Result<Atom, ParseError> {
+        let mut atom = self.term()?;
+        loop {
+            let op = if self.next_if(&Token::Plus) {
+                Operation::Add
+            } else if self.next_if(&Token::Minus) {
+                Operation::Subtract
+            } else {
+                return Ok(atom);
+            };
+            atom = Atom::new_express(atom, self.term()?, op);
+        }
+    }
+    // * and /
+    fn term(&mut self) -> Result<Atom, ParseError> {
+        let mut atom = self.power()?;
+        loop {
+            let op = if self.next_if(&Token::Times) {
+                Operation::Multiply
+            } else if self.next_if(&Token::Divide) {
+                Operation::Divide
+            } else {
+                return Ok(atom);
+            };
+            atom = Atom::new_express(atom, self.power()?, op);
+        }
+    }
+    // ^ and √, right associative
+    fn power(&mut self) -> Result<Atom, ParseError> {
+        let atom = self.unary()?;
+        if self.next_if(&Token::Power) {
+            // negative bases have their own operation
+            let op = match atom.value() {
+                Some(base) if base < 0. => Operation::PowerNeg,
+                _ => Operation::Power,
+            };
+            Ok(Atom::new_express(atom, self.power()?, op))
+        } else if self.next_if(&Token::Root) {
+            Ok(Atom::new_express(atom, self.power()?, Operation::Root))
+        } else {
+            Ok(atom)
+        }
+    }
+    fn unary(&mut self) -> Result<Atom, ParseError> {
+        let mut prefix_funcs = Vec::new();
+        loop {
+            if self.next_if(&Token::SquareRoot) {
+                prefix_funcs.push(Func::SquareRoot);
+            } else if self.next_if(&Token::Summation) {
+                prefix_funcs.push(Func::Summation);
+            } else {
+                break;
+            }
+        }
+        let mut atom = self.primary()?;
+        // the closest function to the value is applied first
+        for func in prefix_funcs.into_iter().rev() {
+            atom.funcs.push(func);
+        }
+        while self.next_if(&Token::Factorial) {
+            atom.funcs.push(Func::Factorial);
+        }
+        Ok(atom)
+    }
+    fn primary(&mut self) -> Result<Atom, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Num(num)) => {
+                self.pos += 1;
+                Ok(Atom::new(num))
+            }
+            // only numbers can be negative, there is no negation function
+            Some(Token::Minus) => match self.tokens.get(self.pos + 1) {
+                Some(Token::Num(num)) => {
+                    let num = -num;
+                    self.pos += 2;
+                    Ok(Atom::new(num))
+                }
+                _ => Err(self.error("expected a number after -".to_string())),
+            },
+            Some(Token::Open) => {
+                self.pos += 1;
+                let atom = self.expr()?;
+                if !self.next_if(&Token::Close) {
+                    return Err(self.error("expected )".to_string()));
+                }
+                Ok(atom)
+            }
+            Some(token) => Err(self.error(format!("unexpected {}", token))),
+            None => Err(self.error("unexpected end of expression".to_string())),
+        }
+    }
+}
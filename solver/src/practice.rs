@@ -0,0 +1,299 @@
+use crate::error::SolveError;
+use crate::finder::atom::Atom;
+use crate::finder::js_eval::{js_check, to_js_string, JsReject};
+use crate::finder::math::within_error;
+use crate::finder::options::SolveOptions;
+use crate::finder::rules::Rules;
+use crate::finder::score::Score;
+use crate::finder::{best_solution, Solution};
+use crate::generator::{Difficulty, Generator};
+use crate::hints::hints_for;
+use crate::parser::{parse_atom, ParseError};
+use std::fmt::{Display, Formatter};
+use std::io::{stdin, stdout, BufRead, Write};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+const HELP: &str = "type an expression to try it, e.g. (1 + 2) * sqrt 9!, or a command:
+:hint    show the next hint
+:reveal  show the solver's best solution
+:best    show your best attempt
+:next    load a new random puzzle
+:help    show this
+:quit    stop practicing";
+
+// reads attempts from stdin until :quit or the end of input
+pub fn run_practice(nums: Vec<f64>, goal: f64, options: SolveOptions) {
+    let mut session = Session::new(nums, goal, options);
+    println!("{}", HELP);
+    session.print_puzzle();
+    prompt();
+    for line in stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim();
+        match line {
+            "" => {}
+            ":quit" => break,
+            ":help" => println!("{}", HELP),
+            ":hint" => println!("{}", session.hint()),
+            ":reveal" => session.reveal(),
+            ":best" => session.print_best(),
+            ":next" => session.next(),
+            _ => session.attempt(line),
+        }
+        prompt();
+    }
+}
+
+fn prompt() {
+    print!("> ");
+    stdout().flush().ok();
+}
+
+enum Solver {
+    Running(Receiver<Result<Option<Solution>, SolveError>>),
+    Done(Option<Solution>),
+}
+
+impl Solver {
+    // the memo isn't Send, so the whole search runs on the other thread
+    fn start(nums: &[f64], goal: f64, options: &SolveOptions) -> Self {
+        let (sender, receiver) = channel();
+        let nums = nums.to_vec();
+        let options = options.clone();
+        // if the puzzle changes first, the result is sent to nobody and dropped
+        thread::spawn(move || sender.send(best_solution(&nums, goal, &options)));
+        Solver::Running(receiver)
+    }
+    // None while the solver is still running
+    fn poll(&mut self) -> Option<Option<&Solution>> {
+        if let Solver::Running(receiver) = self {
+            *self = Solver::Done(finished(receiver.try_recv().ok()?));
+        }
+        let Solver::Done(solution) = self else { unreachable!() };
+        Some(solution.as_ref())
+    }
+    fn wait(&mut self) -> Option<&Solution> {
+        if let Solver::Running(receiver) = self {
+            println!("waiting for the solver...");
+            *self = Solver::Done(receiver.recv().ok().and_then(finished));
+        }
+        let Solver::Done(solution) = self else { unreachable!() };
+        solution.as_ref()
+    }
+}
+
+fn finished(result: Result<Option<Solution>, SolveError>) -> Option<Solution> {
+    result.unwrap_or_else(|err| {
+        println!("the solver failed: {}", err);
+        None
+    })
+}
+
+// one practice puzzle at a time, with the solver working on it in the background
+pub struct Session {
+    nums: Vec<f64>,
+    goal: f64,
+    options: SolveOptions,
+    solver: Solver,
+    best: Option<(Score, Atom)>,
+    hints_given: usize,
+    generator: Option<Generator>,
+}
+
+impl Session {
+    pub fn new(nums: Vec<f64>, goal: f64, options: SolveOptions) -> Self {
+        let solver = Solver::start(&nums, goal, &options);
+        Self {
+            nums,
+            goal,
+            options,
+            solver,
+            best: None,
+            hints_given: 0,
+            generator: None,
+        }
+    }
+    fn print_puzzle(&self) {
+        println!(
+            "goal: {}, nums: {}",
+            self.goal,
+            self.nums
+                .iter()
+                .map(|num| num.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+    fn attempt(&mut self, line: &str) {
+        let Attempt {
+            atom,
+            score,
+            js_warning,
+        } = match check_attempt(line, &self.nums, self.goal, &self.options.rules) {
+            Ok(attempt) => attempt,
+            Err(rejection) => return println!("{}", rejection),
+        };
+        println!("hits the goal with score {}", score);
+        if let Some(reject) = js_warning {
+            println!("javascript might reject that: {}", reject);
+        }
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|(best, _)| score.score() > best.score())
+        {
+            println!("new best!");
+            self.best = Some((score, atom));
+        }
+        match self.solver.poll() {
+            None => println!("the solver is still working"),
+            Some(None) => println!("the solver found nothing, nice"),
+            Some(Some(solution)) if solution.score.score() > score.score() => {
+                println!("the solver's best scores {}", solution.score.score())
+            }
+            Some(Some(_)) => println!("that matches the solver's best!"),
+        }
+    }
+    // the next hint, waiting for the solver if it's still working
+    pub fn hint(&mut self) -> String {
+        let Some(solution) = self.solver.wait() else {
+            return "the solver found no solution to hint at".to_string();
+        };
+        match hints_for(solution).get(self.hints_given) {
+            Some(hint) => {
+                self.hints_given += 1;
+                format!("hint {}: {}", self.hints_given, hint)
+            }
+            None => "no more hints, try :reveal".to_string(),
+        }
+    }
+    fn reveal(&mut self) {
+        match self.solver.wait() {
+            Some(solution) => println!("score {}: {}", solution.score, solution.atom),
+            None => println!("the solver found no solution"),
+        }
+    }
+    fn print_best(&self) {
+        match &self.best {
+            Some((score, atom)) => println!("your best, score {}: {}", score, atom),
+            None => println!("nothing hits the goal yet"),
+        }
+    }
+    fn next(&mut self) {
+        let options = &self.options;
+        let generator = self.generator.get_or_insert_with(|| {
+            let difficulty = Difficulty {
+                min_score: 0,
+                max_score: None,
+                min_solutions: 1,
+                max_solutions: None,
+            };
+            Generator::new(rand::random(), difficulty, options.clone())
+        });
+        println!("finding a puzzle...");
+        let puzzle = match generator.next_puzzle() {
+            Ok(Some(puzzle)) => puzzle,
+            Ok(None) => return println!("couldn't find a new puzzle"),
+            Err(err) => return println!("couldn't find a new puzzle: {}", err),
+        };
+        self.nums = puzzle.nums;
+        self.goal = puzzle.goal;
+        // the generator already solved it
+        self.solver = Solver::Done(Some(puzzle.best));
+        self.best = None;
+        self.hints_given = 0;
+        self.print_puzzle();
+    }
+}
+
+// why an attempt doesn't count, worded for whoever typed it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    Unreadable(ParseError),
+    // uses an operation the rules turn off
+    Disallowed,
+    // uses a number that isn't the puzzle's, or one twice
+    WrongNums,
+    WrongResult { value: f64, goal: f64 },
+    // a step has no value, like dividing by 0
+    NoValue,
+    // hits the goal, but would without one of its functions too
+    UselessFunction,
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Unreadable(err) => write!(f, "couldn't read that: {}", err),
+            Rejection::Disallowed => write!(f, "that uses an operation the rules turn off"),
+            Rejection::WrongNums => write!(f, "use each of the puzzle's numbers at most once"),
+            Rejection::WrongResult { value, goal } => write!(
+                f,
+                "that makes {}, not {}",
+                to_js_string(*value),
+                to_js_string(*goal)
+            ),
+            Rejection::NoValue => write!(f, "one of its steps has no value"),
+            Rejection::UselessFunction => {
+                write!(
+                    f,
+                    "that hits the goal, but one of its functions does nothing"
+                )
+            }
+        }
+    }
+}
+
+// an attempt that counts
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    pub atom: Atom,
+    pub score: Score,
+    // what the javascript guess in finder::js_eval says against it, if anything.
+    // it doesn't stop the attempt counting
+    pub js_warning: Option<JsReject>,
+}
+
+// reads an attempt at the puzzle and checks it with the solver's arithmetic,
+// giving the expression and its score if it counts
+pub fn check_attempt(
+    line: &str,
+    nums: &[f64],
+    goal: f64,
+    rules: &Rules,
+) -> Result<Attempt, Rejection> {
+    let atom = parse_atom(line).map_err(Rejection::Unreadable)?;
+    if !rules.allows_atom(&atom) {
+        return Err(Rejection::Disallowed);
+    }
+    if !uses_only(&atom.nums(), nums) {
+        return Err(Rejection::WrongNums);
+    }
+    match atom.value() {
+        Some(value) if within_error(value, goal) => {}
+        Some(value) => return Err(Rejection::WrongResult { value, goal }),
+        None => return Err(Rejection::NoValue),
+    }
+    if !atom.test(goal) {
+        return Err(Rejection::UselessFunction);
+    }
+    Ok(Attempt {
+        score: atom.get_score(),
+        js_warning: js_check(&atom, goal).err(),
+        atom,
+    })
+}
+
+// whether every number used is one of the puzzle's, each at most once
+fn uses_only(used: &[f64], nums: &[f64]) -> bool {
+    let mut left = nums.to_vec();
+    used.iter()
+        .all(|num| match left.iter().position(|n| n == num) {
+            Some(i) => {
+                left.remove(i);
+                true
+            }
+            None => false,
+        })
+}
@@ -0,0 +1,360 @@
+use crate::archive::Puzzle;
+use crate::error::{InputError, SolveError};
+use crate::finder::options::SolveOptions;
+use crate::finder::stop::Stop;
+use crate::finder::{solve_warm, WarmMemos};
+use crate::generator::{Difficulty, Generator};
+use crate::inputs::{fill_from_date, get_current_date, parse_date};
+use crate::seeding::Seeding;
+use crate::server::verify_json;
+use crate::timezone::Zone;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// json-rpc 2.0 with one message per line, for editors and bots that keep one
+// process running instead of starting the cli for every puzzle. methods:
+//   solve {date, nums, goal}          the report, after a solution notification
+//                                     for each better solution
+//   cancel {id}                       stops a solve or generate, which still
+//                                     answers with what it found
+//   verify {expr, date, nums, goal}   checks an expression like the site
+//   generate {seed, count, minScore, maxScore, minSolutions, maxSolutions}
+//   puzzleForDate {date}
+// every parameter is optional but expr. the puzzle is filled in from the date
+// like the cli does, and the date defaults to today. solves and generates run
+// one at a time on a worker thread that keeps its memos warm, everything else
+// is answered right away
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    // the spec's codes for malformed messages, the cli's exit codes otherwise
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<InputError> for RpcError {
+    fn from(err: InputError) -> Self {
+        RpcError::new(err.exit_code() as i64, err.to_string())
+    }
+}
+
+impl From<SolveError> for RpcError {
+    fn from(err: SolveError) -> Self {
+        RpcError::new(err.exit_code() as i64, err.to_string())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PuzzleParams {
+    date: Option<String>,
+    nums: Option<Vec<f64>>,
+    goal: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyParams {
+    expr: String,
+    #[serde(flatten)]
+    puzzle: PuzzleParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelParams {
+    id: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GenerateParams {
+    seed: Option<u64>,
+    count: usize,
+    min_score: u8,
+    max_score: Option<u8>,
+    min_solutions: usize,
+    max_solutions: Option<usize>,
+}
+
+// the same as the generate command's
+impl Default for GenerateParams {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            count: 1,
+            min_score: 0,
+            max_score: None,
+            min_solutions: 1,
+            max_solutions: None,
+        }
+    }
+}
+
+enum Job {
+    Solve(Puzzle),
+    Generate(GenerateParams),
+}
+
+// a job, with the request's id, or None if nobody wants the answer
+struct Queued {
+    id: Option<Value>,
+    job: Job,
+    stop: Stop,
+}
+
+type Output = Arc<Mutex<Box<dyn Write + Send>>>;
+
+// solves and generates still running or waiting, by their request's id
+type Running = Arc<Mutex<HashMap<String, Stop>>>;
+
+// reads requests until the input ends, then waits for the queued jobs
+pub fn run_rpc(
+    input: impl BufRead,
+    output: Box<dyn Write + Send>,
+    options: &SolveOptions,
+    seeding: &dyn Seeding,
+    zone: Option<Zone>,
+) {
+    let output: Output = Arc::new(Mutex::new(output));
+    let running: Running = Arc::default();
+    let (jobs, queue) = channel();
+    let worker = {
+        let output = output.clone();
+        let running = running.clone();
+        let options = options.clone();
+        // the memo isn't Send, so every solve runs on this one thread
+        thread::spawn(move || work(queue, &output, &running, &options))
+    };
+    let handler = Handler {
+        output,
+        running,
+        options,
+        seeding,
+        zone,
+    };
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(queued) = handler.handle(&line) {
+            jobs.send(queued).unwrap();
+        }
+    }
+    drop(jobs);
+    worker.join().unwrap();
+}
+
+struct Handler<'a> {
+    output: Output,
+    running: Running,
+    options: &'a SolveOptions,
+    seeding: &'a dyn Seeding,
+    zone: Option<Zone>,
+}
+
+impl Handler<'_> {
+    // answers the request, or gives the job for the worker
+    fn handle(&self, line: &str) -> Option<Queued> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => {
+                let err = RpcError::new(PARSE_ERROR, err.to_string());
+                respond(&self.output, Some(Value::Null), Err(err));
+                return None;
+            }
+        };
+        // a request without an id is a notification, which gets no answer
+        let id = request.get("id").cloned();
+        let method = match (&request["jsonrpc"], &request["method"]) {
+            (Value::String(version), Value::String(method)) if version == "2.0" => method,
+            _ => {
+                let err = RpcError::new(INVALID_REQUEST, "not a json-rpc 2.0 request");
+                respond(&self.output, Some(id.unwrap_or(Value::Null)), Err(err));
+                return None;
+            }
+        };
+        let params = request.get("params").cloned().unwrap_or(json!({}));
+        let job = match method.as_str() {
+            "solve" => parse_params(params)
+                .and_then(|params| self.puzzle(params))
+                .map(Job::Solve),
+            "generate" => parse_params(params).map(Job::Generate),
+            "cancel" => {
+                let result = parse_params(params).map(|params| self.cancel(params));
+                respond(&self.output, id, result);
+                return None;
+            }
+            "verify" => {
+                let result = parse_params(params).and_then(|params: VerifyParams| {
+                    let puzzle = self.puzzle(params.puzzle)?;
+                    Ok(verify_json(&puzzle, &params.expr, self.options))
+                });
+                respond(&self.output, id, result);
+                return None;
+            }
+            "puzzleForDate" => {
+                let result = parse_params(params).and_then(|params: PuzzleParams| {
+                    let puzzle = self.puzzle(PuzzleParams {
+                        date: params.date,
+                        ..PuzzleParams::default()
+                    })?;
+                    Ok(json!({ "date": puzzle.date, "goal": puzzle.goal, "nums": puzzle.nums }))
+                });
+                respond(&self.output, id, result);
+                return None;
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        };
+        let job = match job {
+            Ok(job) => job,
+            Err(err) => {
+                respond(&self.output, id, Err(err));
+                return None;
+            }
+        };
+        let stop = Stop::new();
+        if let Some(id) = &id {
+            self.running
+                .lock()
+                .unwrap()
+                .insert(id.to_string(), stop.clone());
+        }
+        Some(Queued { id, job, stop })
+    }
+    fn puzzle(&self, params: PuzzleParams) -> Result<Puzzle, RpcError> {
+        let today = get_current_date(self.zone.as_ref());
+        let date = params
+            .date
+            .map(|date| parse_date(&date, today))
+            .transpose()?
+            .unwrap_or(today);
+        let uses_date = params.nums.is_none() || params.goal.is_none();
+        let (goal, nums) = fill_from_date(params.nums, params.goal, date, self.seeding)?;
+        Ok(Puzzle {
+            goal,
+            nums,
+            date: uses_date.then_some(date),
+        })
+    }
+    fn cancel(&self, params: CancelParams) -> Value {
+        let stop = self.running.lock().unwrap().remove(&params.id.to_string());
+        if let Some(stop) = &stop {
+            stop.stop();
+        }
+        json!({ "cancelled": stop.is_some() })
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+fn work(queue: Receiver<Queued>, output: &Output, running: &Running, options: &SolveOptions) {
+    let mut memos = WarmMemos::new();
+    for Queued { id, job, stop } in queue {
+        let options = SolveOptions {
+            stop: stop.clone(),
+            ..options.clone()
+        };
+        let result = match job {
+            Job::Solve(puzzle) => solve_job(&puzzle, &options, &mut memos, output, &id),
+            Job::Generate(params) => generate_job(params, &options),
+        };
+        if let Some(id) = &id {
+            running.lock().unwrap().remove(&id.to_string());
+        }
+        respond(output, id, result);
+    }
+}
+
+fn solve_job(
+    puzzle: &Puzzle,
+    options: &SolveOptions,
+    memos: &mut WarmMemos,
+    output: &Output,
+    id: &Option<Value>,
+) -> Result<Value, RpcError> {
+    let report = solve_warm(&puzzle.nums, puzzle.goal, options, memos, |score, atom| {
+        if let Some(id) = id {
+            let solution = json!({ "id": id, "score": score.score(), "atom": atom.to_string() });
+            send(output, notification("solution", solution));
+        }
+    })?;
+    let mut result = report.to_json(options.rules.family_ranking);
+    result["stopped"] = json!(options.stop.is_stopped());
+    Ok(result)
+}
+
+// cancelling stops between puzzles, with the ones found so far
+fn generate_job(params: GenerateParams, options: &SolveOptions) -> Result<Value, RpcError> {
+    let seed = params.seed.unwrap_or_else(rand::random);
+    let difficulty = Difficulty {
+        min_score: params.min_score,
+        max_score: params.max_score,
+        min_solutions: params.min_solutions,
+        max_solutions: params.max_solutions,
+    };
+    let mut generator = Generator::new(seed, difficulty, options.clone());
+    let mut puzzles = Vec::new();
+    while puzzles.len() < params.count && !options.stop.is_stopped() {
+        let Some(puzzle) = generator.next_puzzle()? else {
+            break;
+        };
+        puzzles.push(json!({
+            "goal": puzzle.goal,
+            "nums": puzzle.nums,
+            "best_score": puzzle.best.score.score(),
+            "best": puzzle.best.atom.to_string(),
+            "solutions": puzzle.solutions,
+        }));
+    }
+    Ok(json!({ "seed": seed, "puzzles": puzzles }))
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn respond(output: &Output, id: Option<Value>, result: Result<Value, RpcError>) {
+    let Some(id) = id else { return };
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+        }),
+    };
+    send(output, response);
+}
+
+// whoever reads the output going away ends nothing, the input ending does
+fn send(output: &Output, message: Value) {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", message)
+        .and_then(|_| output.flush())
+        .ok();
+}
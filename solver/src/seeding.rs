@@ -0,0 +1,227 @@
+use crate::error::InputError;
+use chrono::{Datelike, NaiveDate};
+use ordered_float::OrderedFloat;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+// how a date turns into a puzzle. the game's own version is SetNumbers, and
+// Formula describes variants of it, e.g. older versions of the game or fan made
+// ones, loaded from a config file
+pub trait Seeding {
+    // both are None if there aren't enough different numbers for the date
+    fn goal(&self, date: NaiveDate) -> Option<f64>;
+    fn nums(&self, date: NaiveDate) -> Option<Vec<f64>>;
+}
+
+// based on https://dateo-math-game.com/setNumbers.js, which the default
+// Formula reproduces
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SetNumbers;
+
+impl Seeding for SetNumbers {
+    fn goal(&self, date: NaiveDate) -> Option<f64> {
+        Formula::default().goal(date)
+    }
+    fn nums(&self, date: NaiveDate) -> Option<Vec<f64>> {
+        Formula::default().nums(date)
+    }
+}
+
+// the same kind of lehmer generator as setNumbers.js with every constant
+// configurable. the defaults are the game's, so an empty config file gives
+// the same puzzles as the game
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Formula {
+    pub multiplier: f64,
+    pub modulus: f64,
+    // the first seed is day * day_weight + month * month_weight + year * year_weight
+    pub day_weight: f64,
+    pub month_weight: f64,
+    pub year_weight: f64,
+    // january is 0, like in javascript
+    pub zero_indexed_month: bool,
+    pub count: usize,
+    // numbers are drawn from 1 to max_num before being negated
+    pub max_num: f64,
+    // a number is negated when the seed is a multiple of this, 0 for never
+    pub negate_every: f64,
+    pub sorted: bool,
+    pub goal: GoalRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GoalRule {
+    // the day of the month, like the game
+    Day,
+    Month,
+    // 1 to 366
+    DayOfYear,
+    // the same goal every day
+    Fixed(f64),
+    // the next number from the generator after the puzzle's numbers, from 1 to
+    // the given maximum
+    Seeded(f64),
+}
+
+impl Default for Formula {
+    fn default() -> Self {
+        Self {
+            multiplier: 16807.,
+            modulus: 2147483647.,
+            day_weight: 1.,
+            month_weight: 1_000_000.,
+            year_weight: 100.,
+            zero_indexed_month: true,
+            count: 5,
+            max_num: 20.,
+            negate_every: 3.,
+            sorted: true,
+            goal: GoalRule::Day,
+        }
+    }
+}
+
+// gives up on a date after this many draws, in case the generator cycles
+// without finding enough different numbers
+const MAX_DRAWS: usize = 10_000;
+
+impl Formula {
+    // reads a toml file with any of the fields, e.g.
+    //   max-num = 12
+    //   goal = { seeded = 24 }
+    pub fn load(path: &Path) -> Result<Self, InputError> {
+        let invalid_file = |reason: String| InputError::InvalidFile {
+            path: path.display().to_string(),
+            reason,
+        };
+        let config = std::fs::read_to_string(path).map_err(|err| invalid_file(err.to_string()))?;
+        let formula: Formula =
+            toml::from_str(&config).map_err(|err| invalid_file(err.to_string()))?;
+        formula
+            .check()
+            .map_err(|reason| invalid_file(reason.to_string()))?;
+        Ok(formula)
+    }
+    fn check(&self) -> Result<(), &'static str> {
+        if !(1..=5).contains(&self.count) {
+            return Err("count has to be from 1 to 5, the most numbers the solver takes");
+        }
+        if self.max_num < self.count as f64 || self.max_num.fract() != 0. {
+            return Err("max-num has to be a whole number, at least count");
+        }
+        if self.modulus < 2. || self.multiplier < 2. {
+            return Err("multiplier and modulus have to be at least 2");
+        }
+        if self.negate_every < 0. {
+            return Err("negate-every can't be negative");
+        }
+        if let GoalRule::Seeded(max) = self.goal {
+            if max < 1. || max.fract() != 0. {
+                return Err("the seeded goal's maximum has to be a whole number, at least 1");
+            }
+        }
+        Ok(())
+    }
+    fn first_seed(&self, date: NaiveDate) -> f64 {
+        let month = if self.zero_indexed_month {
+            date.month0()
+        } else {
+            date.month()
+        };
+        date.day() as f64 * self.day_weight
+            + month as f64 * self.month_weight
+            + date.year() as f64 * self.year_weight
+    }
+    fn next_seed(&self, seed: f64) -> f64 {
+        (seed * self.multiplier) % self.modulus
+    }
+    // the numbers in the order they were drawn and the seed after the last one
+    fn draw(&self, date: NaiveDate) -> Option<(Vec<f64>, f64)> {
+        let mut seed = self.first_seed(date);
+        let mut nums = Vec::new();
+        for _ in 0..MAX_DRAWS {
+            seed = self.next_seed(seed);
+            let mut num = seed % self.max_num + 1.;
+            if self.negate_every > 0. && seed % self.negate_every == 0. {
+                num = -num;
+            }
+            if !nums.contains(&num) {
+                nums.push(num);
+            }
+            if nums.len() == self.count {
+                return Some((nums, seed));
+            }
+        }
+        None
+    }
+}
+
+impl Seeding for Formula {
+    fn goal(&self, date: NaiveDate) -> Option<f64> {
+        let (_, seed) = self.draw(date)?;
+        Some(match self.goal {
+            GoalRule::Day => date.day() as f64,
+            GoalRule::Month => date.month() as f64,
+            GoalRule::DayOfYear => date.ordinal() as f64,
+            GoalRule::Fixed(goal) => goal,
+            GoalRule::Seeded(max) => self.next_seed(seed) % max + 1.,
+        })
+    }
+    fn nums(&self, date: NaiveDate) -> Option<Vec<f64>> {
+        let (mut nums, _) = self.draw(date)?;
+        if self.sorted {
+            nums.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        }
+        Some(nums)
+    }
+}
+
+// every date from `from` to `to` whose puzzle has the numbers, in any order,
+// and the goal if one is given
+pub fn which_dates(
+    seeding: &dyn Seeding,
+    nums: &[f64],
+    goal: Option<f64>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<NaiveDate> {
+    let nums = nums_key(nums.to_vec());
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| {
+            goal.is_none_or(|goal| seeding.goal(*date) == Some(goal))
+                && seeding.nums(*date).map(nums_key).as_ref() == Some(&nums)
+        })
+        .collect()
+}
+
+// the numbers shared by more than one date from `from` to `to`, with the dates,
+// earliest first
+pub fn twin_dates(
+    seeding: &dyn Seeding,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(Vec<f64>, Vec<NaiveDate>)> {
+    let mut dates = HashMap::<_, Vec<NaiveDate>>::new();
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        if let Some(nums) = seeding.nums(date) {
+            dates.entry(nums_key(nums)).or_default().push(date);
+        }
+    }
+    let mut twins = dates
+        .into_iter()
+        .filter(|(_, dates)| dates.len() > 1)
+        .map(|(nums, dates)| (nums.into_iter().map(|num| num.0).collect(), dates))
+        .collect::<Vec<_>>();
+    twins.sort_by_key(|(_, dates)| dates[0]);
+    twins
+}
+
+fn nums_key(nums: Vec<f64>) -> Vec<OrderedFloat<f64>> {
+    let mut nums = nums.into_iter().map(OrderedFloat).collect::<Vec<_>>();
+    nums.sort();
+    nums
+}
@@ -0,0 +1,300 @@
+use crate::archive::{append_to_archive, best_by_date, keep_best, ArchiveEntry, Puzzle};
+use crate::error::InputError;
+use crate::finder::options::SolveOptions;
+use crate::finder::solve;
+use crate::finder::stop::Stop;
+use crate::inputs::{get_current_date, goal_and_nums_for_date, parse_date};
+use crate::practice::check_attempt;
+use crate::seeding::Seeding;
+use crate::timezone::Zone;
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{info, warn};
+
+// a local json api for the solver, so other programs on the machine can use it
+// without starting a process per puzzle. it only listens on 127.0.0.1 and needs
+// nothing from the network:
+//   GET /solve?date=2023-07-04          solutions as server-sent events
+//   GET /solve?nums=1+2+3+4+5&goal=10   the same for given numbers
+//   GET /verify?expr=...&date=...       checks an expression like the site
+//   GET /best?date=2023-07-04           the best result the server or its archive has for a date
+pub struct ServeOptions {
+    pub port: u16,
+    // solves past this are turned away with 503 instead of slowing every one down
+    pub max_concurrent: usize,
+    // a solve is stopped after this long and ends with the best solution so far
+    pub timeout: Duration,
+    // for what today is, None for the system's timezone
+    pub zone: Option<Zone>,
+    // every finished solve is appended to it, like the cli's solves
+    pub archive: Option<PathBuf>,
+}
+
+// what a request asks for, once its parameters are read
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    Solve(Puzzle),
+    Verify { puzzle: Puzzle, expr: String },
+    Best(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<InputError> for ApiError {
+    fn from(err: InputError) -> Self {
+        ApiError::new(400, err.to_string())
+    }
+}
+
+// reads the path and query of a GET request, filling in missing numbers and
+// goals from the date like the cli does. the date defaults to today
+pub fn parse_request(
+    url: &str,
+    today: NaiveDate,
+    seeding: &dyn Seeding,
+) -> Result<Route, ApiError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect::<HashMap<String, String>>();
+    let date = params
+        .get("date")
+        .map(|date| parse_date(date, today))
+        .transpose()?
+        .unwrap_or(today);
+    let puzzle = || -> Result<Puzzle, ApiError> {
+        let goal = params
+            .get("goal")
+            .map(|goal| {
+                goal.trim()
+                    .parse::<f64>()
+                    .map_err(|_| InputError::InvalidNumber(goal.clone()))
+            })
+            .transpose()?;
+        let nums = params.get("nums").cloned();
+        let uses_date = nums.is_none() || goal.is_none();
+        let (goal, nums) = goal_and_nums_for_date(nums, goal, date, seeding)?;
+        Ok(Puzzle {
+            goal,
+            nums,
+            date: uses_date.then_some(date),
+        })
+    };
+    match path.trim_end_matches('/') {
+        "/solve" => Ok(Route::Solve(puzzle()?)),
+        "/verify" => {
+            let expr = params
+                .get("expr")
+                .ok_or_else(|| ApiError::new(400, "missing the expr parameter"))?;
+            Ok(Route::Verify {
+                puzzle: puzzle()?,
+                expr: expr.clone(),
+            })
+        }
+        "/best" => Ok(Route::Best(date)),
+        _ => Err(ApiError::new(
+            404,
+            "unknown path, try /solve, /verify or /best",
+        )),
+    }
+}
+
+// the answer to /verify, which is 200 whether or not the expression counts
+pub fn verify_json(puzzle: &Puzzle, expr: &str, options: &SolveOptions) -> Value {
+    match check_attempt(expr, &puzzle.nums, puzzle.goal, &options.rules) {
+        Ok(attempt) => json!({
+            "valid": true,
+            "atom": attempt.atom.to_string(),
+            "score": attempt.score.score(),
+            "js_warning": attempt.js_warning.map(|reject| reject.to_string()),
+        }),
+        Err(rejection) => json!({ "valid": false, "reason": rejection.to_string() }),
+    }
+}
+
+struct State {
+    running: AtomicUsize,
+    // the best finished solve of each date's own puzzle, starting with the archive's
+    best: Mutex<HashMap<NaiveDate, ArchiveEntry>>,
+}
+
+// counts a solve as running until it's dropped, even if the solve panics
+struct Running(Arc<State>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// answers requests until the process is killed
+pub fn serve(
+    serve: &ServeOptions,
+    options: &SolveOptions,
+    seeding: &dyn Seeding,
+) -> Result<(), InputError> {
+    let best = match &serve.archive {
+        Some(archive) => best_by_date(archive, seeding)?,
+        None => HashMap::new(),
+    };
+    let address = format!("127.0.0.1:{}", serve.port);
+    let server = Server::http(&address).map_err(|err| InputError::CantListen {
+        address: address.clone(),
+        reason: err.to_string(),
+    })?;
+    info!("listening on http://{}", address);
+    let state = Arc::new(State {
+        running: AtomicUsize::new(0),
+        best: Mutex::new(best),
+    });
+    for request in server.incoming_requests() {
+        if *request.method() != Method::Get {
+            respond(
+                request,
+                405,
+                json!({ "error": "only GET requests are answered" }),
+            );
+            continue;
+        }
+        let today = get_current_date(serve.zone.as_ref());
+        let route = match parse_request(request.url(), today, seeding) {
+            Ok(route) => route,
+            Err(err) => {
+                respond(request, err.status, json!({ "error": err.message }));
+                continue;
+            }
+        };
+        match route {
+            Route::Verify { puzzle, expr } => {
+                respond(request, 200, verify_json(&puzzle, &expr, options))
+            }
+            Route::Best(date) => match state.best.lock().unwrap().get(&date) {
+                Some(entry) => respond(request, 200, json!(entry)),
+                None => respond(
+                    request,
+                    404,
+                    json!({ "error": format!("no result for {} yet, solve it first", date) }),
+                ),
+            },
+            Route::Solve(puzzle) => {
+                if state.running.fetch_add(1, Ordering::SeqCst) >= serve.max_concurrent {
+                    state.running.fetch_sub(1, Ordering::SeqCst);
+                    respond(
+                        request,
+                        503,
+                        json!({ "error": "too many puzzles are being solved, try again later" }),
+                    );
+                    continue;
+                }
+                let running = Running(state.clone());
+                let options = SolveOptions {
+                    stop: Stop::with_deadline(Instant::now() + serve.timeout),
+                    ..options.clone()
+                };
+                let archive = serve.archive.clone();
+                let best_for = puzzle.own_date(seeding);
+                // the memo isn't Send, so the whole search runs on the other thread
+                thread::spawn(move || {
+                    let entry = stream_solve(request, &puzzle, &options);
+                    if let Some(entry) = entry {
+                        if let Some(archive) = archive {
+                            if let Err(err) = append_to_archive(&archive, &entry) {
+                                warn!(%err, "couldn't archive the result");
+                            }
+                        }
+                        if let Some(date) = best_for {
+                            keep_best(&mut running.0.best.lock().unwrap(), date, entry);
+                        }
+                    }
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn respond(request: Request, status: u16, body: Value) {
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type("application/json"));
+    // the client hanging up isn't the server's problem
+    request.respond(response).ok();
+}
+
+fn content_type(mime: &str) -> Header {
+    Header::from_bytes("Content-Type", mime).unwrap()
+}
+
+// sends the puzzle, each better solution and the report as events, the same
+// objects as --format json gives. None if the solve failed
+fn stream_solve(request: Request, puzzle: &Puzzle, options: &SolveOptions) -> Option<ArchiveEntry> {
+    // tiny_http buffers response bodies, so the events are written by hand, one
+    // chunk each, so clients can tell where the stream ends
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\n\
+        Content-Type: text/event-stream\r\n\
+        Cache-Control: no-cache\r\n\
+        Transfer-Encoding: chunked\r\n\
+        Connection: close\r\n\r\n";
+    if writer.write_all(head.as_bytes()).is_err() {
+        return None;
+    }
+    let writer = RefCell::new(writer);
+    let write_chunk = |chunk: &str| {
+        let mut writer = writer.borrow_mut();
+        write!(writer, "{:x}\r\n{}\r\n", chunk.len(), chunk).and_then(|_| writer.flush())
+    };
+    // a client that hung up stops the search, nobody is listening anymore
+    let send = |event: &str, data: Value| {
+        if write_chunk(&format!("event: {}\ndata: {}\n\n", event, data)).is_err() {
+            options.stop.stop();
+        }
+    };
+    send(
+        "puzzle",
+        json!({ "date": puzzle.date, "goal": puzzle.goal, "nums": puzzle.nums }),
+    );
+    let best = RefCell::new(None);
+    let report = solve(&puzzle.nums, puzzle.goal, options, |score, atom| {
+        send(
+            "solution",
+            json!({ "score": score.score(), "atom": atom.to_string() }),
+        );
+        *best.borrow_mut() = Some(atom.to_string());
+    });
+    let report = match report {
+        Ok(report) => report,
+        Err(err) => {
+            send("error", json!({ "error": err.to_string() }));
+            write_chunk("").ok();
+            return None;
+        }
+    };
+    let mut done = report.to_json(options.rules.family_ranking);
+    done["stopped"] = json!(options.stop.is_stopped());
+    send("done", done);
+    write_chunk("").ok();
+    Some(ArchiveEntry::new(puzzle, &report, best.into_inner()))
+}
@@ -0,0 +1,129 @@
+use crate::error::InputError;
+use chrono::{DateTime, FixedOffset, NaiveDate, Offset, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// the game picks its puzzle from the browser's local date, so whoever is past
+// midnight first is already on the next puzzle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    // an IANA name, e.g. Europe/Berlin, which follows daylight saving
+    Named(Tz),
+    // a fixed UTC offset, e.g. +05:30
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    pub fn date_at(&self, now: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Zone::Named(tz) => now.with_timezone(tz).date_naive(),
+            Zone::Fixed(offset) => now.with_timezone(offset).date_naive(),
+        }
+    }
+}
+
+impl FromStr for Zone {
+    type Err = InputError;
+    // IANA names first, then offsets like UTC, +2, -08:00, +0530 or UTC+5:30
+    fn from_str(zone: &str) -> Result<Self, Self::Err> {
+        if let Ok(tz) = zone.parse::<Tz>() {
+            return Ok(Zone::Named(tz));
+        }
+        parse_offset(zone)
+            .map(Zone::Fixed)
+            .ok_or_else(|| InputError::UnknownTimeZone(zone.to_string()))
+    }
+}
+
+fn parse_offset(offset: &str) -> Option<FixedOffset> {
+    let upper = offset.trim().to_uppercase();
+    let rest = ["UTC", "GMT", "Z"]
+        .iter()
+        .find_map(|prefix| upper.strip_prefix(prefix))
+        .unwrap_or(&upper);
+    if rest.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, rest) = match rest.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    // the furthest real offsets are -12:00 and +14:00
+    if hours > 14 || minutes >= 60 || (hours == 14 && minutes > 0) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+            Zone::Fixed(offset) => write!(f, "UTC{}", offset),
+        }
+    }
+}
+
+// a date that is today somewhere, with the timezones it is today in
+#[derive(Debug, Clone)]
+pub struct TodaySomewhere {
+    pub date: NaiveDate,
+    // grouped by their current UTC offset, west to east
+    pub zones: Vec<(FixedOffset, Vec<Tz>)>,
+}
+
+// every date that is today somewhere on earth at the given moment, earliest
+// first. there are two, or three from about 10:00 to 12:00 UTC, when UTC+14 is
+// already on the next day and UTC-11/-12 are still on the previous one
+pub fn todays_everywhere(now: DateTime<Utc>) -> Vec<TodaySomewhere> {
+    let mut dates = BTreeMap::<NaiveDate, BTreeMap<i32, Vec<Tz>>>::new();
+    for tz in TZ_VARIANTS.iter().copied().filter(is_geographic) {
+        let local = now.with_timezone(&tz);
+        let offset = local.offset().fix().local_minus_utc();
+        dates
+            .entry(local.date_naive())
+            .or_default()
+            .entry(offset)
+            .or_default()
+            .push(tz);
+    }
+    dates
+        .into_iter()
+        .map(|(date, zones)| TodaySomewhere {
+            date,
+            zones: zones
+                .into_iter()
+                .map(|(offset, tzs)| (FixedOffset::east_opt(offset).unwrap(), tzs))
+                .collect(),
+        })
+        .collect()
+}
+
+// leaves out the old aliases like US/Pacific and the Etc/ zones, which repeat
+// the same offsets as the places that use them
+fn is_geographic(tz: &Tz) -> bool {
+    const AREAS: [&str; 9] = [
+        "Africa",
+        "America",
+        "Antarctica",
+        "Asia",
+        "Atlantic",
+        "Australia",
+        "Europe",
+        "Indian",
+        "Pacific",
+    ];
+    tz.name()
+        .split_once('/')
+        .is_some_and(|(area, _)| AREAS.contains(&area))
+}
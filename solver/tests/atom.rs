@@ -0,0 +1,62 @@
+use dateo_fun_remover::finder::program::Program;
+use dateo_fun_remover::parser::parse_atom;
+
+#[test]
+fn steps_follow_the_evaluation() {
+    let steps = |expression: &str| parse_atom(expression).unwrap().eval_steps();
+    assert_eq!(steps("(2 + 3) * 4"), ["2 + 3 = 5", "5 * 4 = 20"]);
+    assert_eq!(
+        steps("²√(12 + 4)!"),
+        ["12 + 4 = 16", "²√(16) = 4", "!(4) = 24"]
+    );
+    assert_eq!(steps("1 / 0"), ["1 / 0 = NaN"]);
+}
+
+#[test]
+fn programs_evaluate_like_atoms() {
+    for expression in [
+        "(2 + 3) * 4",
+        "²√(12 + 4)!",
+        "2 ^ (3 - 1) + Σ4",
+        "(1 - 2) ^ (1 / 2) + 3",
+        "(5 - 13) ^ ²√(4 + 12) - ²√²√16",
+        "3 √ (1 - 9)",
+        "1 / 0",
+    ] {
+        let atom = parse_atom(expression).unwrap();
+        let program = Program::from_atom(&atom);
+        assert_eq!(program.eval(false), atom.value(), "{}", expression);
+    }
+}
+
+#[test]
+fn every_function_must_be_needed() {
+    let necessary = |expression: &str, goal: f64| {
+        Program::from_atom(&parse_atom(expression).unwrap()).all_funcs_necessary(goal)
+    };
+    assert!(necessary("(2 + 1)!", 6.));
+    assert!(necessary("²√²√16 + 2", 4.));
+    // anything times 0 is still 0
+    assert!(!necessary("(2 - 2) * Σ3", 0.));
+    assert!(!necessary("(3! - 3!) * ²√4", 0.));
+}
+
+#[test]
+fn programs_test_like_trees() {
+    for (expression, goal) in [
+        ("(2 + 1)!", 6.),
+        ("²√²√16 + 2", 4.),
+        ("(2 - 2) * Σ3", 0.),
+        ("(5 - 13) ^ ²√(4 + 12) - ²√²√16", 4094.),
+        ("²√²√²√²√(²√(4 + 12) ^ (13 + Σ²√²√(-2 - -18)))", 4.),
+        ("1 / 0", 0.),
+    ] {
+        let atom = parse_atom(expression).unwrap();
+        assert_eq!(
+            atom.test(goal),
+            atom.test_by_program(goal),
+            "{}",
+            expression
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use dateo_fun_remover::finder::atom::Atom;
+use dateo_fun_remover::finder::bounds::Bound;
+use dateo_fun_remover::finder::func::Func;
+use dateo_fun_remover::finder::operation::Operation;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::rules::Rules;
+use dateo_fun_remover::finder::solve;
+use strum::IntoEnumIterator;
+
+fn options(rules: Rules) -> SolveOptions {
+    SolveOptions {
+        rules,
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    }
+}
+
+// without functions nothing is cut off, so the search covers every expression
+#[test]
+fn basic_rules_are_exact() {
+    let rules = Rules::basic()
+        .disable_func(Func::SquareRoot)
+        .disable_func(Func::Factorial)
+        .disable_func(Func::Summation);
+    let report = solve(&[2., 3., 4.], 10., &options(rules), |_, _| {}).unwrap();
+    assert_eq!(report.best_score, 3);
+    assert!(report.completeness.is_exact(), "{}", report.completeness);
+}
+
+// ²√2 isn't an integer, so it's thrown away and the search can't be exact
+#[test]
+fn thrown_away_fractions_are_a_bound() {
+    let rules = Rules::basic()
+        .disable_func(Func::Factorial)
+        .disable_func(Func::Summation);
+    let report = solve(&[2., 3., 4.], 10., &options(rules), |_, _| {}).unwrap();
+    let bounds_hit = report.completeness.bounds_hit;
+    assert!(
+        bounds_hit.contains(Bound::NonInteger),
+        "{}",
+        report.completeness
+    );
+    assert!(!report.completeness.is_exact());
+}
+
+// every expression of the numbers in the mask, without functions
+fn expressions(nums: &[f64], mask: usize, rules: &Rules) -> Vec<Atom> {
+    if mask.count_ones() == 1 {
+        return vec![Atom::new(nums[mask.trailing_zeros() as usize])];
+    }
+    // every way to split the numbers in two, each side keeping at least one
+    let mut atoms = Vec::new();
+    let mut left_mask = (mask - 1) & mask;
+    while left_mask > 0 {
+        for left in expressions(nums, left_mask, rules) {
+            for right in expressions(nums, mask & !left_mask, rules) {
+                for op in Operation::iter().filter(|op| rules.allows(op)) {
+                    let atom = Atom::new_express(left.clone(), right.clone(), op);
+                    if atom.value().is_some() {
+                        atoms.push(atom);
+                    }
+                }
+            }
+        }
+        left_mask = (left_mask - 1) & mask;
+    }
+    atoms
+}
+
+// vals with the same value can differ in functions, so one passing
+// Atom::test doesn't mean the others do. every one of them has to meet
+#[test]
+fn every_val_with_a_value_meets() {
+    let report = solve(&[2., 4., 4.], 8., &options(Rules::standard()), |_, _| {}).unwrap();
+    assert_eq!(report.best_score, 13);
+}
+
+// the joiners cover every binary tree, so they find the best of every
+// expression. under the standard rules power and root operations score, so
+// vals with the same value can score differently, and only some of them pass
+#[test]
+fn solves_find_the_best_expression() {
+    let rules = Rules::standard()
+        .disable_func(Func::SquareRoot)
+        .disable_func(Func::Factorial)
+        .disable_func(Func::Summation);
+    // with three numbers nothing gets over the maximum number size, so these
+    // are exact too
+    for (nums, goal, exact) in [
+        (vec![2., 3., 4.], 8., true),
+        (vec![2., 2., 2.], 2., true),
+        (vec![3., 3., 3.], 3., true),
+        (vec![-2., 4., 16.], 2., true),
+        (vec![-2., 2., 4.], 4., true),
+        (vec![2., 4., 4.], 16., true),
+        (vec![1., 2., 2., 3.], 4., false),
+    ] {
+        let report = solve(&nums, goal, &options(rules), |_, _| {}).unwrap();
+        let best = (1..1 << nums.len())
+            .flat_map(|mask| expressions(&nums, mask, &rules))
+            .filter(|atom| atom.test(goal))
+            .map(|atom| atom.get_score().score())
+            .max()
+            .unwrap_or(0);
+        assert_eq!(report.best_score, best, "{:?} goal {}", nums, goal);
+        assert_eq!(
+            report.completeness.is_exact(),
+            exact,
+            "{}",
+            report.completeness
+        );
+    }
+}
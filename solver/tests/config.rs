@@ -0,0 +1,114 @@
+use dateo_fun_remover::archive::{append_to_archive, best_by_date, read_archive, ArchiveEntry};
+use dateo_fun_remover::config::{Config, OutputFormat};
+use dateo_fun_remover::finder::best_solution;
+use dateo_fun_remover::finder::func::Func;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::rules::{OperationGroup, RulePreset, Rules};
+use dateo_fun_remover::parser::parse_atom;
+use dateo_fun_remover::seeding::SetNumbers;
+
+#[test]
+fn config_parses() {
+    let config = Config::parse(
+        r#"
+        format = "json"
+        preset = "quick"
+        tz = "Europe/Berlin"
+        rules = "basic"
+        disable = ["multiplication"]
+        disable-functions = ["factorial", "summation"]
+        archive = "results.jsonl"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.format, Some(OutputFormat::Json));
+    assert_eq!(config.preset, Some(Preset::Quick));
+    assert_eq!(config.tz.as_deref(), Some("Europe/Berlin"));
+    assert_eq!(config.rules, Some(RulePreset::Basic));
+    assert_eq!(config.disable, [OperationGroup::Multiplication]);
+    assert_eq!(config.disable_functions, [Func::Factorial, Func::Summation]);
+    assert_eq!(Config::parse(&config.to_toml()), Ok(config));
+    assert_eq!(Config::parse(""), Ok(Config::default()));
+    assert!(Config::parse("preset = \"slow\"").is_err());
+    assert!(Config::parse("colour = \"blue\"").is_err());
+}
+
+#[test]
+fn flags_override_the_file() {
+    let file = Config::parse("preset = \"quick\"\ndisable = [\"power\"]\ntz = \"+02:00\"").unwrap();
+    let flags = Config {
+        preset: Some(Preset::Exhaustive),
+        disable: vec![OperationGroup::Root],
+        ..Config::default()
+    };
+    let config = file.overridden_by(flags).with_defaults();
+    assert_eq!(config.preset, Some(Preset::Exhaustive));
+    assert_eq!(config.disable, [OperationGroup::Root]);
+    assert_eq!(config.tz.as_deref(), Some("+02:00"));
+    assert_eq!(config.format, Some(OutputFormat::Text));
+    assert_eq!(config.rules, Some(RulePreset::Standard));
+}
+
+#[test]
+fn disabled_functions_are_left_out() {
+    let rules = Rules::standard().disable_func(Func::Factorial);
+    assert!(!rules.allows_atom(&parse_atom("3! + 2").unwrap()));
+    assert!(rules.allows_atom(&parse_atom("sqrt 9 + sum 2").unwrap()));
+    let options = SolveOptions {
+        rules,
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    };
+    let best = best_solution(&[-14., -3., 2., 7., 14.], 12., &options)
+        .unwrap()
+        .unwrap();
+    assert!(rules.allows_atom(&best.atom), "{}", best.atom);
+    assert!(best.atom.test(12.));
+}
+
+#[test]
+fn archive_round_trips() {
+    let path = std::env::temp_dir().join("dateo_archive_test.jsonl");
+    std::fs::remove_file(&path).ok();
+    let entry = ArchiveEntry {
+        solved_at: chrono::Utc::now(),
+        date: chrono::NaiveDate::from_ymd_opt(2023, 3, 8),
+        goal: 8.,
+        nums: vec![-18., -2., 9., 11., 20.],
+        best_score: 19,
+        best: Some("²√9".to_string()),
+        completeness: "best score 19".to_string(),
+    };
+    append_to_archive(&path, &entry).unwrap();
+    append_to_archive(&path, &entry).unwrap();
+    let archive = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(read_archive(&archive), Ok(vec![entry.clone(), entry]));
+    std::fs::remove_file(&path).unwrap();
+}
+
+// a restarted server answers /best from what earlier runs archived
+#[test]
+fn best_results_load_from_the_archive() {
+    let path = std::env::temp_dir().join("dateo_best_archive_test.jsonl");
+    std::fs::remove_file(&path).ok();
+    let day = chrono::NaiveDate::from_ymd_opt(2023, 3, 8).unwrap();
+    assert!(best_by_date(&path, &SetNumbers).unwrap().is_empty());
+    let entry = |best_score, goal| ArchiveEntry {
+        solved_at: chrono::Utc::now(),
+        date: Some(day),
+        goal,
+        nums: vec![-18., -2., 9., 11., 20.],
+        best_score,
+        best: None,
+        completeness: String::new(),
+    };
+    append_to_archive(&path, &entry(19, 8.)).unwrap();
+    append_to_archive(&path, &entry(21, 8.)).unwrap();
+    append_to_archive(&path, &entry(18, 8.)).unwrap();
+    // not the date's own puzzle
+    append_to_archive(&path, &entry(30, 9.)).unwrap();
+    let best = best_by_date(&path, &SetNumbers).unwrap();
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[&day].best_score, 21);
+    std::fs::remove_file(&path).unwrap();
+}
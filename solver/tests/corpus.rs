@@ -0,0 +1,63 @@
+use dateo_fun_remover::corpus::{check_entry, diff_entry, parse_corpus};
+use dateo_fun_remover::finder::options::Engine;
+
+#[test]
+fn corpus_parses() {
+    let entries = parse_corpus(include_str!("../corpus.txt")).unwrap();
+    assert!(!entries.is_empty());
+    // the goal of a date puzzle is its day
+    let christmas = entries
+        .iter()
+        .find(|entry| entry.name == "date 2023-12-25")
+        .unwrap();
+    assert_eq!(christmas.goal, 25.);
+    assert_eq!(christmas.nums.len(), 5);
+}
+
+#[test]
+fn corpus_takes_1_to_5_nums() {
+    let entries = parse_corpus("nums 7 goal 7 quick 19\nnums 2 3 4 goal 24 quick 19").unwrap();
+    assert_eq!(entries[0].nums, vec![7.]);
+    assert_eq!(entries[1].name, "nums 2 3 4 goal 24");
+    assert_eq!(entries[1].nums, vec![2., 3., 4.]);
+    assert_eq!(entries[1].goal, 24.);
+    assert_eq!(
+        parse_corpus("nums 1 2 3 4 5 6 goal 5 quick 10").unwrap_err(),
+        "line 1: please provide 1 to 5 numbers, got 6"
+    );
+}
+
+#[test]
+fn corpus_rejects_bad_lines() {
+    assert!(parse_corpus("date 2023-02-30 normal 19").is_err());
+    assert!(parse_corpus("nums 1 2 3 4 5 6 goal 5 quick 10").is_err());
+    assert!(parse_corpus("nums goal 5 quick 10").is_err());
+    assert!(parse_corpus("nums 1 2 3 4 5 quick 10").is_err());
+    assert!(parse_corpus("nums 1 2 goal quick 10").is_err());
+    assert!(parse_corpus("date 2023-03-08 slow 19").is_err());
+    assert!(parse_corpus("date 2023-03-08 normal").is_err());
+}
+
+// solves every puzzle, one after another since some of them take a lot of memory
+#[test]
+fn corpus_scores_hold() {
+    let entries = parse_corpus(include_str!("../corpus.txt")).unwrap();
+    let regressions = entries
+        .iter()
+        .map(|entry| check_entry(entry).unwrap())
+        .filter(|result| result.is_regression())
+        .map(|result| result.to_string())
+        .collect::<Vec<_>>();
+    assert!(regressions.is_empty(), "{}", regressions.join("\n"));
+}
+
+// the legacy engine searches less than the joiners, so it finding more is a bug
+#[test]
+fn legacy_engine_isnt_ahead() {
+    let entries = parse_corpus("nums -18 -2 4 12 13 goal 4 quick 20").unwrap();
+    let result = diff_entry(&entries[0]).unwrap();
+    assert!(!result.is_failure(), "{}", result);
+    assert!(result.is_disagreement());
+    assert_eq!(result.ahead().unwrap().engine, Engine::Joiner);
+    assert!(result.legacy.best.is_some());
+}
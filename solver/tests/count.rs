@@ -0,0 +1,48 @@
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::{best_solution, best_solution_with_count};
+
+const NUMS: [f64; 5] = [-18., -2., 4., 12., 13.];
+
+// the squares trick finds an expression the joiners don't
+#[test]
+fn strategy_solutions_are_counted() {
+    let with_strategies = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    };
+    let without_strategies = SolveOptions {
+        strategies: Vec::new(),
+        ..with_strategies.clone()
+    };
+    let (_, general) = best_solution_with_count(&NUMS, 4., &without_strategies, 100_000).unwrap();
+    let (best, all) = best_solution_with_count(&NUMS, 4., &with_strategies, 100_000).unwrap();
+    assert!(all > general);
+    assert_eq!(
+        best.unwrap().score.score(),
+        best_solution(&NUMS, 4., &with_strategies)
+            .unwrap()
+            .unwrap()
+            .score
+            .score()
+    );
+}
+
+// the best solution is still searched for after the count stops at the limit
+#[test]
+fn the_limit_stops_only_the_count() {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        strategies: Vec::new(),
+        ..SolveOptions::default()
+    };
+    let (best, count) = best_solution_with_count(&NUMS, 4., &options, 3).unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(
+        best.unwrap().score.score(),
+        best_solution(&NUMS, 4., &options)
+            .unwrap()
+            .unwrap()
+            .score
+            .score()
+    );
+}
@@ -0,0 +1,44 @@
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::rules::FamilyRanking;
+use dateo_fun_remover::finder::solve;
+
+// with only 5 square roots allowed, ²√(-2 - -18) ^ 2^n keeps going past the cap
+#[test]
+fn families_are_checked_and_found_below_the_best_score() {
+    let mut search = SearchOptions::from_preset(Preset::Quick);
+    search.max_square_roots = 5;
+    let options = SolveOptions {
+        search,
+        ..SolveOptions::default()
+    };
+    let report = solve(&[-18., -2., 4., 12., 13.], 4., &options, |_, _| {}).unwrap();
+    assert!(!report.families.is_empty());
+    for family in &report.families {
+        assert!(family.largest_atom.test(4.));
+        assert!(family.largest_square_roots > family.square_roots);
+        assert_eq!(
+            family.largest_atom.get_score().score(),
+            family.largest_score
+        );
+        assert!(family.largest_score > family.score.score());
+        assert!(family.score_step() > 0.);
+        assert!(family.to_string().starts_with("score grows by "));
+    }
+    // which powers of 2 can be built skips around, so a search that assumes
+    // it doesn't would stop short of 13
+    assert_eq!(
+        report
+            .families
+            .iter()
+            .map(|family| family.largest_square_roots)
+            .max(),
+        Some(13)
+    );
+    // the found instances all score below the best solution
+    assert!(report
+        .families
+        .iter()
+        .all(|family| family.score.score() < report.best_score));
+    assert!(report.ranked_score(FamilyRanking::Largest) >= report.best_score);
+    assert_eq!(report.ranked_score(FamilyRanking::Found), report.best_score);
+}
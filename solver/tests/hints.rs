@@ -0,0 +1,81 @@
+use dateo_fun_remover::finder::best_solution;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::hints::{hints_for, Hint};
+use dateo_fun_remover::practice::Session;
+
+const PUZZLES: [(&[f64], f64); 4] = [
+    (&[2., 3., 4.], 24.),
+    (&[1., 5., 9.], 2.),
+    (&[3., 7., 8., 12.], 10.),
+    (&[-2., 4., 9., 11.], 6.),
+];
+
+fn options() -> SolveOptions {
+    SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    }
+}
+
+// the order hints come in, weakest first
+fn strength(hint: &Hint) -> usize {
+    match hint {
+        Hint::Numbers(_) => 0,
+        Hint::FirstOperation(_) => 1,
+        Hint::Functions(_) => 2,
+        Hint::Intermediate(_) => 3,
+        Hint::Outline { .. } => 4,
+    }
+}
+
+#[test]
+fn hints_get_stronger_without_giving_the_solution_away() {
+    for (nums, goal) in PUZZLES {
+        let solution = best_solution(nums, goal, &options()).unwrap().unwrap();
+        let hints = hints_for(&solution);
+        let shown = hints
+            .iter()
+            .map(|hint| hint.to_string())
+            .collect::<Vec<_>>();
+        assert!(matches!(hints[0], Hint::Numbers(_)), "{:?}", shown);
+        assert!(
+            hints
+                .windows(2)
+                .all(|pair| strength(&pair[0]) < strength(&pair[1])),
+            "{:?}",
+            shown
+        );
+
+        let atom = solution.atom.to_string();
+        let (last, before) = shown.split_last().unwrap();
+        assert!(
+            before.iter().all(|hint| !hint.contains(&atom)),
+            "{:?}",
+            shown
+        );
+        // even the outline holds back the numbers that fill its hole
+        let Some(Hint::Outline { outer, inner }) = hints.last() else {
+            panic!("no outline in {:?}", shown);
+        };
+        assert!(inner.nums().is_empty(), "{}", last);
+        assert!(outer.nums().len() < solution.atom.nums().len(), "{}", last);
+        assert!(!last.contains(&atom), "{}", last);
+    }
+}
+
+#[test]
+fn practice_gives_hints_in_order() {
+    for (nums, goal) in PUZZLES {
+        let solution = best_solution(nums, goal, &options()).unwrap().unwrap();
+        let expected = hints_for(&solution)
+            .iter()
+            .enumerate()
+            .map(|(i, hint)| format!("hint {}: {}", i + 1, hint))
+            .collect::<Vec<_>>();
+
+        let mut session = Session::new(nums.to_vec(), goal, options());
+        let given = expected.iter().map(|_| session.hint()).collect::<Vec<_>>();
+        assert_eq!(given, expected);
+        assert_eq!(session.hint(), "no more hints, try :reveal");
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use dateo_fun_remover::error::{InputError, SolveError};
+use dateo_fun_remover::finder::best_solution;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::inputs::{get_goal_and_nums, parse_date};
+
+fn nums(nums: &str) -> Result<(f64, Vec<f64>), InputError> {
+    get_goal_and_nums(
+        Some(nums.to_string()),
+        Some(7.),
+        Some(8),
+        Some(3),
+        Some(2023),
+    )
+}
+
+#[test]
+fn bad_inputs_are_errors() {
+    assert_eq!(
+        nums("1 2 x 4 5"),
+        Err(InputError::InvalidNumber("x".to_string()))
+    );
+    assert_eq!(nums(""), Err(InputError::WrongNumberCount(0)));
+    assert_eq!(nums("1 2 3 4 5 6"), Err(InputError::WrongNumberCount(6)));
+    assert_eq!(nums("1 2 3 4 1e300"), Err(InputError::OutOfRange(1e300)));
+    assert_eq!(nums("1 2 3 4 NaN").unwrap_err().exit_code(), 13);
+    assert_eq!(
+        get_goal_and_nums(None, None, Some(30), Some(2), Some(2023)),
+        Err(InputError::InvalidDate {
+            year: 2023,
+            month: 2,
+            day: 30
+        })
+    );
+    assert!(get_goal_and_nums(None, None, Some(1), Some(0), Some(2023)).is_err());
+}
+
+#[test]
+fn good_inputs_parse() {
+    assert_eq!(
+        nums("-18 -2  9 11 20"),
+        Ok((7., vec![-18., -2., 9., 11., 20.]))
+    );
+    assert!(get_goal_and_nums(None, None, Some(29), Some(2), Some(2024)).is_ok());
+    assert_eq!(nums("2 3 4"), Ok((7., vec![2., 3., 4.])));
+    assert_eq!(nums("3 3 5"), Ok((7., vec![3., 3., 5.])));
+}
+
+#[test]
+fn small_and_repeated_puzzles_solve() {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    };
+    let three = best_solution(&[2., 3., 4.], 7., &options).unwrap().unwrap();
+    assert!(three.atom.test(7.));
+    // both 3s can be used
+    let repeated = best_solution(&[3., 3.], 9., &options).unwrap().unwrap();
+    assert!(repeated.atom.test(9.));
+    assert!(best_solution(&[2.], 7., &options).is_ok());
+}
+
+#[test]
+fn solver_rejects_bad_nums() {
+    let options = SolveOptions::default();
+    assert_eq!(
+        best_solution(&[], 7., &options).unwrap_err(),
+        SolveError::UnsupportedNumberCount(0)
+    );
+    assert_eq!(
+        best_solution(&[1., 2., f64::INFINITY], 7., &options).unwrap_err(),
+        SolveError::Input(InputError::OutOfRange(f64::INFINITY))
+    );
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn full_dates_parse() {
+    // a wednesday
+    let today = date(2023, 3, 8);
+    let parse = |full_date| parse_date(full_date, today);
+    assert_eq!(parse("2021-10-01"), Ok(date(2021, 10, 1)));
+    assert_eq!(parse("today"), Ok(today));
+    assert_eq!(parse("Yesterday"), Ok(date(2023, 3, 7)));
+    assert_eq!(parse("tomorrow"), Ok(date(2023, 3, 9)));
+    assert_eq!(parse("+30"), Ok(date(2023, 4, 7)));
+    assert_eq!(parse("-8"), Ok(date(2023, 2, 28)));
+    assert_eq!(parse("wednesday"), Ok(today));
+    assert_eq!(parse("mon"), Ok(date(2023, 3, 6)));
+    assert_eq!(parse("Thursday"), Ok(date(2023, 3, 2)));
+    for bad in ["2023-02-30", "someday", "+x", "-99999999999"] {
+        assert_eq!(parse(bad), Err(InputError::UnknownDate(bad.to_string())));
+    }
+}
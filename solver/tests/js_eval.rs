@@ -0,0 +1,32 @@
+use dateo_fun_remover::finder::js_eval::{js_check, to_js_string, JsReject};
+use dateo_fun_remover::parser::parse_atom;
+
+#[test]
+fn formats_numbers_like_javascript() {
+    assert_eq!(to_js_string(8.), "8");
+    assert_eq!(to_js_string(-2.5), "-2.5");
+    assert_eq!(to_js_string(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(to_js_string(0.000001), "0.000001");
+    assert_eq!(to_js_string(0.0000001), "1e-7");
+    assert_eq!(to_js_string(1e20), "100000000000000000000");
+    assert_eq!(to_js_string(1e21), "1e+21");
+    assert_eq!(to_js_string(1.5e300), "1.5e+300");
+    assert_eq!(to_js_string(f64::NAN), "NaN");
+    assert_eq!(to_js_string(f64::NEG_INFINITY), "-Infinity");
+}
+
+#[test]
+fn rejects_what_the_game_rejects() {
+    let check = |expression: &str, goal: f64| js_check(&parse_atom(expression).unwrap(), goal);
+    assert_eq!(check("(2 + 3) * 4", 20.), Ok(()));
+    assert_eq!(check("2 + 3", 6.), Err(JsReject::WrongResult(5.)));
+    assert_eq!(check("1 / 0", 0.), Err(JsReject::Infinite));
+    assert_eq!(check("²√(1 - 5)", 2.), Err(JsReject::NotANumber));
+    assert_eq!(check("(2 / 4)!", 1.), Err(JsReject::NotWhole(0.5)));
+    // Math.pow has no real odd roots of negative numbers
+    assert_eq!(check("3 √ (1 - 9)", -2.), Err(JsReject::NotANumber));
+    assert!(matches!(
+        check("1 / 1000000", 0.),
+        Err(JsReject::NearZero(_))
+    ));
+}
@@ -0,0 +1,40 @@
+use dateo_fun_remover::finder::bounds::{Bound, BoundSet};
+use dateo_fun_remover::finder::joiner::Memo;
+
+#[test]
+fn removing_a_goal_keeps_other_goals() {
+    let mut memo = Memo::new();
+    for key in [
+        "G 1",
+        "G (N[0] 3 G 1)",
+        "G (G 1 N[0] 3)",
+        "G 10",
+        "G 1.5",
+        "G -1",
+        "G (N[0] 3 G 12)",
+        "N (N[0] 1 N[1] 2)",
+    ] {
+        memo.insert(key.to_string(), Vec::new());
+    }
+    memo.remove_goal(1.);
+    assert_eq!(memo.len(), 5);
+    assert!(memo.get("G 1").is_none() && memo.get("G (G 1 N[0] 3)").is_none());
+    assert!(memo.get("G 10").is_some() && memo.get("G (N[0] 3 G 12)").is_some());
+    assert!(memo.get("N (N[0] 1 N[1] 2)").is_some());
+}
+
+#[test]
+fn goal_bounds_stay_with_their_goal() {
+    let mut memo = Memo::new();
+    let mut depth = BoundSet::new();
+    depth.insert(Bound::Depth);
+    let mut num_size = BoundSet::new();
+    num_size.insert(Bound::NumSize);
+    memo.bounds.extend(num_size);
+    memo.extend_goal_bounds("G 1", depth);
+    assert!(memo.bounds_for(1.).contains(Bound::Depth));
+    assert!(memo.bounds_for(1.).contains(Bound::NumSize));
+    assert!(!memo.bounds_for(10.).contains(Bound::Depth));
+    memo.remove_goal(1.);
+    assert!(!memo.bounds_for(1.).contains(Bound::Depth));
+}
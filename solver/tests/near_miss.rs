@@ -0,0 +1,23 @@
+use dateo_fun_remover::finder::near_miss::MissCause;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::solve;
+
+// 2 only reaches 2, Σ2 = 3, 3! = Σ3 = 6 and bigger values within the quick depth
+#[test]
+fn near_miss_reads_the_memo() {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        strategies: Vec::new(),
+        ..SolveOptions::default()
+    };
+    let report = solve(&[2.], 7., &options, |_, _| {}).unwrap();
+    assert_eq!(report.best_score, 0);
+    let near_miss = report.near_miss.unwrap();
+    let below = near_miss.below.unwrap();
+    assert_eq!(below.value, 6.);
+    assert!(below.atom.test(6.));
+    assert!(near_miss.above.unwrap().value > 7.);
+    assert!(matches!(near_miss.cause, MissCause::Bounds(bounds) if !bounds.is_empty()));
+    // reading the memo doesn't search again
+    assert_eq!(report.stats.joiners, 1);
+}
@@ -0,0 +1,39 @@
+use dateo_fun_remover::finder::js_eval::JsReject;
+use dateo_fun_remover::finder::rules::Rules;
+use dateo_fun_remover::parser::parse_atom;
+use dateo_fun_remover::practice::{check_attempt, Rejection};
+
+#[test]
+fn javascript_only_warns() {
+    let rules = Rules::default();
+    // the solver's arithmetic has odd roots of negative numbers, Math.pow doesn't
+    let expression = "(1 - 9) ^ (1 / 3)";
+    assert!(parse_atom(expression).unwrap().test(-2.));
+    let attempt = check_attempt(expression, &[1., 9., 1., 3.], -2., &rules).unwrap();
+    assert_eq!(
+        attempt.score.score(),
+        parse_atom(expression).unwrap().get_score().score()
+    );
+    assert_eq!(attempt.js_warning, Some(JsReject::NotANumber));
+
+    let attempt = check_attempt("(2 + 3) * 4", &[2., 3., 4.], 20., &rules).unwrap();
+    assert_eq!(attempt.js_warning, None);
+}
+
+#[test]
+fn attempts_are_rejected_by_the_solvers_arithmetic() {
+    let rules = Rules::default();
+    let check = |line: &str, goal: f64| {
+        check_attempt(line, &[1., 2., 3., 4., 5.], goal, &rules).unwrap_err()
+    };
+    assert_eq!(
+        check("2 + 3", 6.),
+        Rejection::WrongResult {
+            value: 5.,
+            goal: 6.
+        }
+    );
+    assert_eq!(check("4 / (1 + 2 - 3)", 0.), Rejection::NoValue);
+    assert_eq!(check("1 + 1", 2.), Rejection::WrongNums);
+    assert_eq!(check("(1 + 2 - 3) * Σ4", 0.), Rejection::UselessFunction);
+}
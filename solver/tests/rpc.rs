@@ -0,0 +1,208 @@
+use dateo_fun_remover::finder::bounds::Bound;
+use dateo_fun_remover::finder::func::Func;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::rules::{OperationGroup, Rules};
+use dateo_fun_remover::finder::{solve, solve_warm, WarmMemos};
+use dateo_fun_remover::rpc::run_rpc;
+use dateo_fun_remover::seeding::SetNumbers;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+// output the test can read back after the rpc thread is done with it
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn quick() -> SolveOptions {
+    SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        ..SolveOptions::default()
+    }
+}
+
+// every message written for the requests, in order
+fn run(requests: &[Value]) -> Vec<Value> {
+    let input = requests
+        .iter()
+        .map(|request| format!("{}\n", request))
+        .collect::<String>();
+    run_input(&input)
+}
+
+fn run_input(input: &str) -> Vec<Value> {
+    let output = Shared::default();
+    run_rpc(
+        input.as_bytes(),
+        Box::new(output.clone()),
+        &quick(),
+        &SetNumbers,
+        None,
+    );
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn request(id: i64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn response(messages: &[Value], id: i64) -> &Value {
+    messages.iter().find(|message| message["id"] == id).unwrap()
+}
+
+#[test]
+fn quick_methods_answer() {
+    let messages = run(&[
+        request(1, "puzzleForDate", json!({ "date": "2023-07-04" })),
+        request(
+            2,
+            "verify",
+            json!({ "expr": "1 + 2", "nums": [1, 2, 3, 4, 5], "goal": 3 }),
+        ),
+        request(
+            3,
+            "verify",
+            json!({ "expr": "2 + 2", "date": "2023-07-04" }),
+        ),
+        request(4, "cancel", json!({ "id": 99 })),
+        json!({ "jsonrpc": "2.0", "method": "cancel", "params": { "id": 1 } }),
+    ]);
+    assert_eq!(messages.len(), 4);
+    assert_eq!(
+        response(&messages, 1)["result"],
+        json!({ "date": "2023-07-04", "goal": 4.0, "nums": [-18.0, -2.0, 4.0, 12.0, 13.0] })
+    );
+    assert_eq!(response(&messages, 2)["result"]["score"], 2);
+    assert_eq!(response(&messages, 3)["result"]["valid"], false);
+    assert_eq!(response(&messages, 4)["result"]["cancelled"], false);
+}
+
+#[test]
+fn bad_requests_get_errors() {
+    let messages = run_input("not json\n{\"id\": 1, \"method\": \"solve\"}\n");
+    assert_eq!(messages[0]["error"]["code"], -32700);
+    assert_eq!(messages[1]["error"]["code"], -32600);
+
+    let messages = run(&[
+        request(1, "guess", json!({})),
+        request(2, "solve", json!({ "nums": [1, 2, 3, 4, 5, 6] })),
+        request(3, "verify", json!({ "goal": 3 })),
+        request(4, "puzzleForDate", json!({ "date": "someday" })),
+    ]);
+    assert_eq!(response(&messages, 1)["error"]["code"], -32601);
+    assert_eq!(response(&messages, 2)["error"]["code"], 11);
+    assert_eq!(response(&messages, 3)["error"]["code"], -32602);
+    assert_eq!(response(&messages, 4)["error"]["code"], 16);
+}
+
+#[test]
+fn solves_notify_and_cancel() {
+    let messages = run(&[
+        request(
+            1,
+            "solve",
+            json!({ "nums": [-14, -3, 2, 7, 14], "goal": 12 }),
+        ),
+        request(2, "solve", json!({ "date": "2023-07-04" })),
+        request(3, "cancel", json!({ "id": 2 })),
+    ]);
+    let solutions = messages
+        .iter()
+        .filter(|message| message["method"] == "solution" && message["params"]["id"] == 1)
+        .collect::<Vec<_>>();
+    let result = &response(&messages, 1)["result"];
+    assert!(!solutions.is_empty());
+    assert_eq!(
+        solutions.last().unwrap()["params"]["score"],
+        result["best_score"]
+    );
+    assert_eq!(result["stopped"], false);
+    assert_eq!(response(&messages, 3)["result"]["cancelled"], true);
+    assert_eq!(response(&messages, 2)["result"]["stopped"], true);
+}
+
+// the stop belongs to the cancelled solve, not the memos it leaves behind
+#[test]
+fn cancelling_doesnt_stop_the_next_solve() {
+    let messages = run(&[
+        request(1, "solve", json!({ "date": "2023-07-04" })),
+        request(2, "cancel", json!({ "id": 1 })),
+        request(3, "solve", json!({ "date": "2023-07-04" })),
+    ]);
+    let cancelled = &response(&messages, 1)["result"];
+    assert_eq!(cancelled["stopped"], true);
+    assert!(cancelled["completeness"]
+        .as_str()
+        .unwrap()
+        .contains("search stopped early"));
+    let again = &response(&messages, 3)["result"];
+    assert_eq!(again["stopped"], false);
+    assert!(!again["completeness"]
+        .as_str()
+        .unwrap()
+        .contains("search stopped early"));
+}
+
+#[test]
+fn warm_memos_find_the_same() {
+    let options = quick();
+    let nums = [-14., -3., 2., 7., 14.];
+    let mut memos = WarmMemos::new();
+    for goal in [12., 11., 12.] {
+        let cold = solve(&nums, goal, &options, |_, _| {}).unwrap();
+        let warm = solve_warm(&nums, goal, &options, &mut memos, |_, _| {}).unwrap();
+        assert_eq!(warm.best_score, cold.best_score, "goal {}", goal);
+        assert_eq!(
+            warm.completeness.to_string(),
+            cold.completeness.to_string(),
+            "goal {}",
+            goal
+        );
+        assert!(!memos.is_empty());
+    }
+}
+
+// the squares trick goes over the maximum number size here and the general
+// search doesn't, so a warm solve without squares must not report it
+#[test]
+fn limit_hits_stay_with_their_memo() {
+    let rules = Rules::from_groups([OperationGroup::Addition, OperationGroup::Power])
+        .disable_func(Func::Factorial)
+        .disable_func(Func::Summation);
+    let options = SolveOptions {
+        rules,
+        anytime: true,
+        ..quick()
+    };
+    let without_squares = SolveOptions {
+        strategies: Vec::new(),
+        anytime: false,
+        ..options.clone()
+    };
+    let mut memos = WarmMemos::new();
+    let with_squares = solve_warm(&[2., 3.], 2., &options, &mut memos, |_, _| {}).unwrap();
+    assert!(with_squares.strategies[0]
+        .bounds_hit
+        .contains(Bound::NumSize));
+    assert!(with_squares
+        .completeness
+        .bounds_hit
+        .contains(Bound::NumSize));
+
+    let cold = solve(&[2., 3.], 2., &without_squares, |_, _| {}).unwrap();
+    let warm = solve_warm(&[2., 3.], 2., &without_squares, &mut memos, |_, _| {}).unwrap();
+    assert!(!cold.completeness.bounds_hit.contains(Bound::NumSize));
+    assert_eq!(warm.completeness.to_string(), cold.completeness.to_string());
+}
@@ -0,0 +1,114 @@
+use chrono::NaiveDate;
+use dateo_fun_remover::error::InputError;
+use dateo_fun_remover::seeding::{twin_dates, which_dates, Formula, GoalRule, Seeding, SetNumbers};
+use std::path::Path;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn set_numbers_matches_the_site() {
+    let day = date(2023, 3, 8);
+    assert_eq!(SetNumbers.goal(day), Some(8.));
+    assert_eq!(SetNumbers.nums(day), Some(vec![-18., -2., 9., 11., 20.]));
+    let day = date(2024, 2, 29);
+    assert_eq!(SetNumbers.goal(day), Some(29.));
+    assert_eq!(SetNumbers.nums(day), Some(vec![-5., 1., 11., 13., 14.]));
+    let day = date(2030, 12, 31);
+    assert_eq!(SetNumbers.goal(day), Some(31.));
+    assert_eq!(SetNumbers.nums(day), Some(vec![-2., 1., 2., 9., 13.]));
+}
+
+#[test]
+fn example_formula_is_the_default() {
+    let formula = Formula::default();
+    let example = Formula::load(Path::new("seeding.example.toml")).unwrap();
+    assert_eq!(example, formula);
+}
+
+#[test]
+fn formulas_change_the_puzzle() {
+    let day = date(2023, 3, 8);
+    let formula = Formula {
+        count: 4,
+        max_num: 9.,
+        negate_every: 0.,
+        goal: GoalRule::Seeded(12.),
+        ..Formula::default()
+    };
+    let nums = formula.nums(day).unwrap();
+    assert_eq!(nums.len(), 4);
+    assert!(nums.iter().all(|num| (1. ..=9.).contains(num)));
+    let goal = formula.goal(day).unwrap();
+    assert!((1. ..=12.).contains(&goal) && goal.fract() == 0.);
+    let day_of_year = Formula {
+        goal: GoalRule::DayOfYear,
+        ..Formula::default()
+    };
+    assert_eq!(day_of_year.goal(day), Some(67.));
+    // a multiplier of the modulus sends every seed to 0
+    let stuck = Formula {
+        multiplier: 7.,
+        modulus: 7.,
+        ..Formula::default()
+    };
+    assert_eq!(stuck.nums(day), None);
+}
+
+#[test]
+fn bad_formulas_are_errors() {
+    let err = Formula::load(Path::new("no such file.toml")).unwrap_err();
+    assert!(matches!(err, InputError::InvalidFile { .. }));
+    let path = std::env::temp_dir().join("dateo_bad_seeding.toml");
+    for config in [
+        "count = 9",
+        "max-num = 2.5",
+        "goal = \"week\"",
+        "colour = 3",
+    ] {
+        std::fs::write(&path, config).unwrap();
+        let err = Formula::load(&path).unwrap_err();
+        assert!(matches!(err, InputError::InvalidFile { .. }), "{}", config);
+    }
+    std::fs::write(&path, "max-num = 12\ngoal = { fixed = 24 }").unwrap();
+    let formula = Formula::load(&path).unwrap();
+    assert_eq!(formula.max_num, 12.);
+    assert_eq!(formula.goal, GoalRule::Fixed(24.));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn dates_are_found_from_their_numbers() {
+    let (from, to) = (date(1900, 1, 1), date(2100, 12, 31));
+    let nums = [20., -2., 11., 9., -18.];
+    assert_eq!(
+        which_dates(&SetNumbers, &nums, None, from, to),
+        [date(2023, 3, 8)]
+    );
+    assert_eq!(
+        which_dates(&SetNumbers, &nums, Some(8.), from, to),
+        [date(2023, 3, 8)]
+    );
+    assert!(which_dates(&SetNumbers, &nums, Some(7.), from, to).is_empty());
+}
+
+#[test]
+fn twins_share_numbers() {
+    let (from, to) = (date(2000, 1, 1), date(2049, 12, 31));
+    let twins = twin_dates(&SetNumbers, from, to);
+    assert!(!twins.is_empty());
+    for (nums, dates) in &twins {
+        assert!(dates.len() > 1);
+        for day in dates {
+            let mut day_nums = SetNumbers.nums(*day).unwrap();
+            day_nums.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(&day_nums, nums);
+        }
+    }
+    for (nums, dates) in twins.iter().take(10) {
+        assert_eq!(&which_dates(&SetNumbers, nums, None, from, to), dates);
+    }
+    // earliest first
+    assert!(twins.windows(2).all(|pair| pair[0].1[0] <= pair[1].1[0]));
+}
@@ -0,0 +1,101 @@
+use chrono::NaiveDate;
+use dateo_fun_remover::archive::Puzzle;
+use dateo_fun_remover::finder::bounds::Bound;
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::solve;
+use dateo_fun_remover::finder::stop::Stop;
+use dateo_fun_remover::seeding::SetNumbers;
+use dateo_fun_remover::server::{parse_request, verify_json, Route};
+use std::time::{Duration, Instant};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn requests_parse() {
+    let today = date(2023, 7, 4);
+    let solve_today = Route::Solve(Puzzle {
+        goal: 4.,
+        nums: vec![-18., -2., 4., 12., 13.],
+        date: Some(today),
+    });
+    assert_eq!(
+        parse_request("/solve", today, &SetNumbers),
+        Ok(solve_today.clone())
+    );
+    assert_eq!(
+        parse_request("/solve/?date=2023-07-04", date(2024, 1, 1), &SetNumbers),
+        Ok(solve_today)
+    );
+    assert_eq!(
+        parse_request("/solve?nums=1+2+3+4+5&goal=%2D10", today, &SetNumbers),
+        Ok(Route::Solve(Puzzle {
+            goal: -10.,
+            nums: vec![1., 2., 3., 4., 5.],
+            date: None,
+        }))
+    );
+    assert_eq!(
+        parse_request("/best?date=yesterday", today, &SetNumbers),
+        Ok(Route::Best(date(2023, 7, 3)))
+    );
+    let Ok(Route::Verify { puzzle, expr }) = parse_request(
+        "/verify?goal=7&expr=%C2%B2%E2%88%9A9+%2B+4",
+        today,
+        &SetNumbers,
+    ) else {
+        panic!("verify didn't parse");
+    };
+    assert_eq!(expr, "²√9 + 4");
+    assert_eq!((puzzle.goal, puzzle.date), (7., Some(today)));
+}
+
+#[test]
+fn bad_requests_are_rejected() {
+    let today = date(2023, 7, 4);
+    let status = |url: &str| parse_request(url, today, &SetNumbers).unwrap_err().status;
+    assert_eq!(status("/"), 404);
+    assert_eq!(status("/solve?nums=1+2+3+4+5+6"), 400);
+    assert_eq!(status("/solve?goal=ten"), 400);
+    assert_eq!(status("/best?date=someday"), 400);
+    assert_eq!(status("/verify?date=2023-07-04"), 400);
+}
+
+#[test]
+fn expressions_are_verified() {
+    let puzzle = Puzzle {
+        goal: 3.,
+        nums: vec![1., 2., 3., 4., 5.],
+        date: None,
+    };
+    let options = SolveOptions::default();
+    let valid = verify_json(&puzzle, "1 + 2", &options);
+    assert_eq!(valid["valid"], true);
+    assert_eq!(valid["score"], 2);
+    assert!(valid["js_warning"].is_null());
+    for expr in ["1 + 1 + 1", "2 + 2", "1 +", "sqrt 1 + 2"] {
+        let invalid = verify_json(&puzzle, expr, &options);
+        assert_eq!(invalid["valid"], false, "{}", expr);
+        assert!(invalid["reason"].is_string());
+    }
+}
+
+#[test]
+fn stopped_searches_end_early() {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        stop: Stop::with_deadline(Instant::now()),
+        ..SolveOptions::default()
+    };
+    let started = Instant::now();
+    let report = solve(&[-18., -2., 4., 12., 13.], 4., &options, |_, _| {}).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(report.completeness.bounds_hit.contains(Bound::Stopped));
+    assert!(report.near_miss.is_none());
+
+    let stop = Stop::new();
+    assert!(!stop.is_stopped());
+    stop.clone().stop();
+    assert!(stop.is_stopped());
+}
@@ -0,0 +1,50 @@
+use dateo_fun_remover::finder::options::{Preset, SearchOptions, SolveOptions};
+use dateo_fun_remover::finder::solve;
+
+#[test]
+fn stats_add_up() {
+    let options = SolveOptions {
+        search: SearchOptions::from_preset(Preset::Quick),
+        timed: true,
+        ..SolveOptions::default()
+    };
+    let report = solve(&[-18., -2., 4., 12., 13.], 4., &options, |_, _| {}).unwrap();
+    let stats = &report.stats;
+    let phases = stats
+        .phases
+        .iter()
+        .map(|(phase, _)| phase.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(phases, ["squares", "other"]);
+    let num_counts = stats
+        .num_counts
+        .iter()
+        .map(|(nums, _)| *nums)
+        .collect::<Vec<_>>();
+    assert_eq!(num_counts, [5, 4, 3, 2, 1]);
+    assert!(stats.joiners > 0 && stats.permutations >= stats.joiners);
+    assert!(stats.memo_misses > 0 && stats.vals > 0);
+    assert!(!stats.top_keys.is_empty() && stats.top_keys.len() <= 5);
+    assert!(stats.top_keys.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    assert!(stats.top_keys.iter().map(|(_, vals)| vals).sum::<u64>() <= stats.vals);
+    assert!(stats.intersections > 0);
+    assert!(stats.tested >= stats.accepted && stats.accepted > 0);
+    assert!(stats.peak_memo > 0);
+    assert_eq!(
+        report.to_json(options.rules.family_ranking)["stats"]["joiners"],
+        stats.joiners
+    );
+
+    // a solve only counts its own work
+    let again = solve(&[-18., -2., 4., 12., 13.], 4., &options, |_, _| {}).unwrap();
+    assert_eq!(again.stats.joiners, stats.joiners);
+
+    // the clock is only read when asked for, wasm has none
+    let untimed = SolveOptions {
+        timed: false,
+        ..options
+    };
+    let report = solve(&[-18., -2., 4., 12., 13.], 4., &untimed, |_, _| {}).unwrap();
+    assert!(report.stats.phases.is_empty() && report.stats.num_counts.is_empty());
+    assert_eq!(report.stats.joiners, stats.joiners);
+}
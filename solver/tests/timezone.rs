@@ -0,0 +1,69 @@
+use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+use dateo_fun_remover::error::InputError;
+use dateo_fun_remover::timezone::{todays_everywhere, Zone};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn zones_parse() {
+    let offset = |secs| Ok(Zone::Fixed(FixedOffset::east_opt(secs).unwrap()));
+    assert_eq!(
+        "Europe/Berlin".parse(),
+        Ok(Zone::Named(chrono_tz::Europe::Berlin))
+    );
+    assert_eq!("UTC".parse(), Ok(Zone::Named(chrono_tz::UTC)));
+    assert_eq!("+2".parse(), offset(2 * 3600));
+    assert_eq!("-08:00".parse(), offset(-8 * 3600));
+    assert_eq!("+0530".parse(), offset(5 * 3600 + 30 * 60));
+    assert_eq!("utc+5:45".parse(), offset(5 * 3600 + 45 * 60));
+    assert_eq!("GMT-3".parse(), offset(-3 * 3600));
+    for bad in ["Mars/Olympus", "+15", "+05:60", "5"] {
+        assert_eq!(
+            bad.parse::<Zone>(),
+            Err(InputError::UnknownTimeZone(bad.to_string()))
+        );
+    }
+}
+
+#[test]
+fn dates_follow_the_zone() {
+    // late evening in new york is already the next day in tokyo
+    let now = Utc.with_ymd_and_hms(2023, 3, 9, 2, 0, 0).unwrap();
+    let zone = |zone: &str| zone.parse::<Zone>().unwrap().date_at(now);
+    assert_eq!(zone("America/New_York"), date(2023, 3, 8));
+    assert_eq!(zone("Asia/Tokyo"), date(2023, 3, 9));
+    assert_eq!(zone("-03:00"), date(2023, 3, 8));
+    assert_eq!(zone("+00:00"), date(2023, 3, 9));
+}
+
+#[test]
+fn every_zone_has_one_today() {
+    // 23:30 in american samoa and 00:30 in kiritimati
+    let now = Utc.with_ymd_and_hms(2023, 3, 9, 10, 30, 0).unwrap();
+    let todays = todays_everywhere(now);
+    let dates = todays.iter().map(|today| today.date).collect::<Vec<_>>();
+    assert_eq!(
+        dates,
+        [date(2023, 3, 8), date(2023, 3, 9), date(2023, 3, 10)]
+    );
+    let zones = todays
+        .iter()
+        .flat_map(|today| today.zones.iter().flat_map(|(_, zones)| zones))
+        .collect::<Vec<_>>();
+    assert!(zones.contains(&&chrono_tz::Pacific::Kiritimati));
+    assert!(!zones.contains(&&chrono_tz::US::Pacific));
+    let mut names = zones.iter().map(|tz| tz.name()).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), zones.len());
+    // offsets run west to east
+    for today in &todays {
+        let offsets = today
+            .zones
+            .iter()
+            .map(|(offset, _)| offset.local_minus_utc());
+        assert!(offsets.clone().zip(offsets.skip(1)).all(|(a, b)| a < b));
+    }
+}
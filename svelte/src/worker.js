@@ -20,18 +20,23 @@ getBindings().then((bindings) => {
   let { solve_with_date, solve_with_goal_and_nums } = bindings;
   onmessage = ({ data }) => {
     if (data.message === 'start') {
-      if (data.useDate) {
-        let [year, month, day] = data.date.split('-');
-        solve_with_date(year, month, day);
-      } else {
-        solve_with_goal_and_nums(
-          data.goal,
-          data.num1,
-          data.num2,
-          data.num3,
-          data.num4,
-          data.num5
-        );
+      try {
+        if (data.useDate) {
+          let [year, month, day] = data.date.split('-');
+          solve_with_date(year, month, day);
+        } else {
+          solve_with_goal_and_nums(
+            data.goal,
+            data.num1,
+            data.num2,
+            data.num3,
+            data.num4,
+            data.num5
+          );
+        }
+      } catch (err) {
+        postMessage({ message: 'error', error: err.message });
+        postMessage({ message: 'done' });
       }
     }
   };